@@ -0,0 +1,25 @@
+//! Derive macros of the `exec_duration` crate, use them through its `derive` feature.
+
+#![deny(missing_docs)]
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, DeriveInput};
+
+/// Implement `exec_duration::Timed` for a type, the probe is named after the type.
+#[proc_macro_derive(Timed)]
+pub fn derive_timed(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let ident = &input.ident;
+    let name = ident.to_string();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let expanded = quote! {
+        impl #impl_generics ::exec_duration::Timed for #ident #ty_generics #where_clause {
+            fn probe_name() -> &'static str {
+                #name
+            }
+        }
+    };
+    expanded.into()
+}
@@ -0,0 +1,170 @@
+use crate::output::DurationUnit;
+use std::any::Any;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+pub(crate) type Aggregator = Arc<dyn Fn(&mut CustomAgg, DurationUnit) + Send + Sync>;
+
+trait AnyValue: Any + Send + Sync {
+    fn clone_box(&self) -> Box<dyn AnyValue>;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Any + Clone + Send + Sync> AnyValue for T {
+    fn clone_box(&self) -> Box<dyn AnyValue> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Custom aggregation state of a probe
+///
+/// An instance is kept per probe name and handed to the closure registered with
+/// `exec_duration::set_aggregator` each time an execution of that probe is reported.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::CustomAgg;
+///
+/// // count executions longer than 1ms
+/// exec_duration::set_aggregator("main", |agg: &mut CustomAgg, duration| {
+///     if duration > 1_000_000 {
+///         *agg.get_or_default::<u64>() += 1;
+///     }
+/// });
+/// ```
+#[derive(Default)]
+pub struct CustomAgg {
+    value: Option<Box<dyn AnyValue>>,
+}
+
+impl CustomAgg {
+    /// Get the aggregated value if it is set and of type `T`
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration::CustomAgg;
+    ///
+    /// let mut agg = CustomAgg::default();
+    /// assert!(agg.get::<u64>().is_none());
+    /// agg.set(10u64);
+    /// assert_eq!(agg.get::<u64>(), Some(&10));
+    /// ```
+    pub fn get<T: Any>(&self) -> Option<&T> {
+        self.value
+            .as_ref()
+            .and_then(|v| (**v).as_any().downcast_ref::<T>())
+    }
+
+    /// Get the aggregated value, initializing it with `T::default()` if it is unset or of another type
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration::CustomAgg;
+    ///
+    /// let mut agg = CustomAgg::default();
+    /// *agg.get_or_default::<u64>() += 1;
+    /// assert_eq!(agg.get::<u64>(), Some(&1));
+    /// ```
+    pub fn get_or_default<T: Any + Clone + Default + Send + Sync>(&mut self) -> &mut T {
+        let is_t = self
+            .value
+            .as_ref()
+            .is_some_and(|v| (**v).as_any().is::<T>());
+        if !is_t {
+            self.value = Some(Box::new(T::default()));
+        }
+        self.value
+            .as_mut()
+            .and_then(|v| (**v).as_any_mut().downcast_mut::<T>())
+            .unwrap()
+    }
+
+    /// Replace the aggregated value
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration::CustomAgg;
+    ///
+    /// let mut agg = CustomAgg::default();
+    /// agg.set(String::from("value"));
+    /// assert_eq!(agg.get::<String>().unwrap(), "value");
+    /// ```
+    pub fn set<T: Any + Clone + Send + Sync>(&mut self, value: T) {
+        self.value = Some(Box::new(value));
+    }
+
+    pub(crate) fn snapshot(&self) -> Option<CustomValue> {
+        self.value
+            .as_ref()
+            .map(|v| CustomValue(Arc::from((**v).clone_box())))
+    }
+}
+
+impl Clone for CustomAgg {
+    fn clone(&self) -> Self {
+        CustomAgg {
+            value: self.value.as_ref().map(|v| (**v).clone_box()),
+        }
+    }
+}
+
+impl fmt::Debug for CustomAgg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomAgg")
+            .field("is_set", &self.value.is_some())
+            .finish()
+    }
+}
+
+/// Snapshot of a custom aggregate attached to the results.
+/// Being opaque, it never takes part in comparisons nor in hashing.
+#[derive(Clone)]
+pub(crate) struct CustomValue(Arc<dyn AnyValue>);
+
+impl CustomValue {
+    pub(crate) fn as_any(&self) -> &dyn Any {
+        (*self.0).as_any()
+    }
+}
+
+impl fmt::Debug for CustomValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CustomValue")
+    }
+}
+
+impl PartialEq for CustomValue {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for CustomValue {}
+
+impl PartialOrd for CustomValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CustomValue {
+    fn cmp(&self, _: &Self) -> Ordering {
+        Ordering::Equal
+    }
+}
+
+impl Hash for CustomValue {
+    fn hash<H: Hasher>(&self, _: &mut H) {}
+}
@@ -0,0 +1,11 @@
+use crate::output::ExecDuration;
+use criterion::measurement::{Measurement, WallTime};
+
+pub(crate) fn to_measurements(
+    results: &[ExecDuration],
+) -> Vec<(String, <WallTime as Measurement>::Value)> {
+    results
+        .iter()
+        .map(|r| (r.get_name().to_string(), r.get_total_duration()))
+        .collect()
+}
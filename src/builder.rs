@@ -0,0 +1,197 @@
+use crate::clock::Clock;
+use crate::manager;
+use crate::ExecProbe;
+use std::time::Duration;
+
+/// Execution probe builder.
+/// Instances are created using `ExecProbe::builder` function.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ExecProbe;
+///
+/// let mut ep = ExecProbe::builder("function_1").record_points(false).build();
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ExecProbeBuilder {
+    name: String,
+    labels: manager::Labels,
+    record_points: bool,
+    merge_points: bool,
+    warmup: u64,
+    clock: Clock,
+    deadline: Option<Duration>,
+    slower_than: Option<Duration>,
+}
+
+impl ExecProbeBuilder {
+    pub(crate) fn new(name: &str) -> Self {
+        ExecProbeBuilder {
+            name: name.to_string(),
+            labels: manager::Labels::new(),
+            record_points: true,
+            merge_points: false,
+            warmup: 0,
+            clock: Clock::default(),
+            deadline: None,
+            slower_than: None,
+        }
+    }
+
+    /// Add a label, the executions of a probe are aggregated separately for each distinct set of labels
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::builder("http_request")
+    ///     .label("method", "GET")
+    ///     .label("status", "200")
+    ///     .build();
+    /// ep.add_point("line 1");
+    /// ```
+    pub fn label(mut self, key: &str, value: &str) -> Self {
+        self.labels.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Enable or disable points recording (enabled by default)
+    ///
+    /// When disabled, `add_point` calls are kept cheap and only the total execution duration is reported.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::builder("main").record_points(false).build();
+    /// ep.add_point("line 1"); // discarded
+    /// ```
+    pub fn record_points(mut self, enabled: bool) -> Self {
+        self.record_points = enabled;
+        self
+    }
+
+    /// Merge the points sharing the same name within an execution (disabled by default)
+    ///
+    /// A point added again accumulates into the first one, which keeps the points of a probe calling `add_point`
+    /// in a loop to one entry per distinct name. The aggregated metrics are the same, see `Point::get_hits`.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::builder("main").merge_points(true).build();
+    /// for _ in 0..100 {
+    ///     ep.add_point("iter");
+    /// }
+    /// assert_eq!(ep.points().len(), 1);
+    /// ```
+    pub fn merge_points(mut self, enabled: bool) -> Self {
+        self.merge_points = enabled;
+        self
+    }
+
+    /// Discard the first `n` executions reported under the probe name (none by default)
+    ///
+    /// Warmup executions do not contribute to the metrics, measurement proceeds normally afterwards.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// for _ in 0..10 {
+    ///     let mut ep = ExecProbe::builder("main").warmup(3).build();
+    ///     ep.add_point("line 1");
+    /// }
+    /// ```
+    pub fn warmup(mut self, n: u64) -> Self {
+        self.warmup = n;
+        self
+    }
+
+    /// Set the clock timestamping the points of the probe (`Clock::System` by default)
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::{Clock, ExecProbe};
+    ///
+    /// let mut ep = ExecProbe::builder("hot_path").clock(Clock::Coarse).build();
+    /// ep.add_point("line 1");
+    /// ```
+    pub fn clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Abandon the execution if it lasts longer than `deadline` (no deadline by default)
+    ///
+    /// An abandoned execution is not reported, even when the probe is eventually stopped, which protects the metrics
+    /// from probes leaking out of their scope. `flush_live` and `fetch_results` also abandon the probes alive past
+    /// their deadline.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    /// use std::time::Duration;
+    ///
+    /// let mut ep = ExecProbe::builder("request").deadline(Duration::from_secs(30)).build();
+    /// ep.add_point("line 1");
+    /// ```
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Only aggregate the executions lasting longer than `threshold` (all executions by default)
+    ///
+    /// Faster executions are not part of the metrics, which then describe the slow tail alone;
+    /// they are counted separately, see `ExecDuration::get_fast_count`.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    /// use std::time::Duration;
+    ///
+    /// let mut ep = ExecProbe::builder("request")
+    ///     .record_if_slower_than(Duration::from_millis(500))
+    ///     .build();
+    /// ep.add_point("line 1");
+    /// ```
+    pub fn record_if_slower_than(mut self, threshold: Duration) -> Self {
+        self.slower_than = Some(threshold);
+        self
+    }
+
+    /// Create the execution probe, exec duration will be computed from this point
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::builder("main").build();
+    /// ep.add_point("line 1");
+    /// ```
+    pub fn build(self) -> ExecProbe {
+        let mut data = manager::ExecData::with_clock(&self.name, self.clock);
+        data.record_points = self.record_points;
+        data.merge_points = self.merge_points;
+        data.warmup = self.warmup;
+        data.labels = self.labels;
+        data.deadline = self.deadline;
+        data.slower_than = self.slower_than;
+        data.track_live();
+        ExecProbe {
+            data,
+            stop_done: false,
+        }
+    }
+}
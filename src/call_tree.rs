@@ -0,0 +1,105 @@
+use crate::manager;
+use crate::output::{DurationUnit, ExecDuration};
+use std::cell::RefCell;
+use std::time::Instant;
+
+thread_local! {
+    static STACK: RefCell<Vec<Frame>> = const { RefCell::new(Vec::new()) };
+}
+
+struct Frame {
+    name: String,
+    begin: Instant,
+}
+
+/// Push a call on the stack of the current thread
+pub(crate) fn enter(name: &str) {
+    let frame = Frame {
+        name: name.to_string(),
+        begin: Instant::now(),
+    };
+    STACK.with(|s| s.borrow_mut().push(frame));
+}
+
+/// Pop the innermost call of the current thread and record it under its callers
+pub(crate) fn exit() {
+    let call = STACK.with(|s| {
+        let mut stack = s.borrow_mut();
+        let frame = stack.pop()?;
+        let mut path: Vec<String> = stack.iter().map(|f| f.name.to_string()).collect();
+        path.push(frame.name);
+        Some((path, frame.begin.elapsed().as_nanos()))
+    });
+    if let Some((path, duration)) = call {
+        manager::with_instance(|ctx| ctx.record_call(&path, duration));
+    }
+}
+
+/// Aggregated calls, by call path
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CallTree {
+    roots: Vec<CallNode>,
+}
+
+#[derive(Clone, Debug)]
+struct CallNode {
+    name: String,
+    count: u64,
+    duration: DurationUnit,
+    children: Vec<CallNode>,
+}
+
+impl CallNode {
+    fn child<'a>(nodes: &'a mut Vec<CallNode>, name: &str) -> &'a mut CallNode {
+        match nodes.iter().position(|n| n.name == name) {
+            Some(i) => &mut nodes[i],
+            None => {
+                nodes.push(CallNode {
+                    name: name.to_string(),
+                    count: 0,
+                    duration: 0,
+                    children: Vec::new(),
+                });
+                nodes.last_mut().unwrap()
+            }
+        }
+    }
+
+    fn to_exec_duration(&self, total: DurationUnit) -> ExecDuration {
+        let mut elt = ExecDuration::new(&self.name, self.count, self.duration, total);
+        for child in self.children.iter() {
+            elt.add(child.to_exec_duration(self.duration));
+        }
+        elt
+    }
+}
+
+impl CallTree {
+    /// Record a call given its path from the outermost caller, the callers are recorded when they exit
+    pub(crate) fn record(&mut self, path: &[String], duration: DurationUnit) {
+        let (last, callers) = match path.split_last() {
+            Some(split) => split,
+            None => return,
+        };
+        let mut nodes = &mut self.roots;
+        for name in callers.iter() {
+            nodes = &mut CallNode::child(nodes, name).children;
+        }
+        let node = CallNode::child(nodes, last);
+        node.count += 1;
+        node.duration += duration;
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.roots.is_empty()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.roots.clear();
+    }
+
+    /// Results of the outermost calls, holding their callees as elements
+    pub(crate) fn results(&self) -> impl Iterator<Item = ExecDuration> + '_ {
+        self.roots.iter().map(|r| r.to_exec_duration(r.duration))
+    }
+}
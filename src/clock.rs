@@ -0,0 +1,145 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::{Duration, SystemTime};
+
+static ANOMALY_POLICY: AtomicU8 = AtomicU8::new(ClockAnomalyPolicy::Drop as u8);
+
+/// Clock used by a probe to timestamp its points, see `ExecProbeBuilder::clock`.
+/// Timestamps are only compared to each other, so every clock is expressed as a `SystemTime`.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::{Clock, ExecProbe};
+///
+/// let mut ep = ExecProbe::builder("hot_path").clock(Clock::Coarse).build();
+/// ep.add_point("line 1");
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub enum Clock {
+    /// Precise system clock
+    #[default]
+    System,
+    /// Monotonic clock of millisecond resolution (`CLOCK_MONOTONIC_COARSE`), much cheaper to read:
+    /// durations below the resolution are measured as zero nanoseconds. Only effective on Linux, `System` otherwise
+    Coarse,
+    /// CPU time stamp counter (`rdtsc`), the cheapest option. Cycles are converted to approximate nanoseconds
+    /// using a ratio calibrated against the monotonic clock on first use, which takes about 10ms.
+    /// The conversion assumes an invariant counter: frequency scaling on older CPUs, and migration between cores
+    /// whose counters are not synchronized, skew the measurements. Only effective on x86_64, `System` otherwise
+    #[cfg(feature = "cycles")]
+    Cycles,
+}
+
+impl Clock {
+    pub(crate) fn now(&self) -> SystemTime {
+        match self {
+            Clock::System => SystemTime::now(),
+            Clock::Coarse => coarse_now(),
+            #[cfg(feature = "cycles")]
+            Clock::Cycles => cycles_now(),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn coarse_now() -> SystemTime {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // CLOCK_MONOTONIC_COARSE is always available since Linux 2.6.32
+    unsafe {
+        libc::clock_gettime(libc::CLOCK_MONOTONIC_COARSE, &mut ts);
+    }
+    SystemTime::UNIX_EPOCH + Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn coarse_now() -> SystemTime {
+    SystemTime::now()
+}
+
+/// Nanoseconds per cycle of the time stamp counter, measured once against the monotonic clock
+#[cfg(all(feature = "cycles", target_arch = "x86_64"))]
+fn nanos_per_cycle() -> f64 {
+    static CALIBRATION: std::sync::OnceLock<f64> = std::sync::OnceLock::new();
+    *CALIBRATION.get_or_init(|| {
+        let begin = std::time::Instant::now();
+        // SAFETY: the time stamp counter is available on every x86_64 CPU
+        let first = unsafe { core::arch::x86_64::_rdtsc() };
+        while begin.elapsed() < Duration::from_millis(10) {
+            std::hint::spin_loop();
+        }
+        let last = unsafe { core::arch::x86_64::_rdtsc() };
+        let elapsed = begin.elapsed().as_nanos() as f64;
+        elapsed / last.saturating_sub(first).max(1) as f64
+    })
+}
+
+#[cfg(all(feature = "cycles", target_arch = "x86_64"))]
+fn cycles_now() -> SystemTime {
+    let factor = nanos_per_cycle();
+    // SAFETY: the time stamp counter is available on every x86_64 CPU
+    let cycles = unsafe { core::arch::x86_64::_rdtsc() };
+    SystemTime::UNIX_EPOCH + Duration::from_nanos((cycles as f64 * factor) as u64)
+}
+
+#[cfg(all(feature = "cycles", not(target_arch = "x86_64")))]
+fn cycles_now() -> SystemTime {
+    SystemTime::now()
+}
+
+/// Behavior of the probes when the clock goes backward between two timestamps, see
+/// `exec_duration::set_clock_anomaly_policy`
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ClockAnomalyPolicy;
+///
+/// exec_duration::set_clock_anomaly_policy(ClockAnomalyPolicy::Zero);
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+#[non_exhaustive]
+pub enum ClockAnomalyPolicy {
+    /// The anomalous point or execution is discarded
+    #[default]
+    Drop,
+    /// The anomalous delta is recorded as zero, the next ones are measured from the anomalous timestamp
+    Zero,
+    /// Timestamps are clamped to the latest one seen, as if the time stood still until the clock catches up
+    Saturate,
+}
+
+impl ClockAnomalyPolicy {
+    /// Time elapsed from `earlier` to `later` and the timestamp the next delta is measured from,
+    /// `None` when the delta is discarded
+    pub(crate) fn elapsed(
+        self,
+        earlier: SystemTime,
+        later: SystemTime,
+    ) -> Option<(Duration, SystemTime)> {
+        match later.duration_since(earlier) {
+            Ok(d) => Some((d, later)),
+            Err(_) => match self {
+                ClockAnomalyPolicy::Drop => None,
+                ClockAnomalyPolicy::Zero => Some((Duration::ZERO, later)),
+                ClockAnomalyPolicy::Saturate => Some((Duration::ZERO, earlier)),
+            },
+        }
+    }
+}
+
+pub(crate) fn anomaly_policy() -> ClockAnomalyPolicy {
+    match ANOMALY_POLICY.load(Ordering::Relaxed) {
+        1 => ClockAnomalyPolicy::Zero,
+        2 => ClockAnomalyPolicy::Saturate,
+        _ => ClockAnomalyPolicy::Drop,
+    }
+}
+
+pub(crate) fn set_anomaly_policy(policy: ClockAnomalyPolicy) {
+    ANOMALY_POLICY.store(policy as u8, Ordering::Relaxed);
+}
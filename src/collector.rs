@@ -0,0 +1,51 @@
+use crate::manager;
+use crate::output::ExecDuration;
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    static COLLECTORS: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Uninstalls the collector when dropped, including on unwinding
+struct CollectorGuard {
+    id: u64,
+    closed: bool,
+}
+
+impl CollectorGuard {
+    /// Close the collector, returns the metrics it collected
+    fn close(mut self) -> Vec<ExecDuration> {
+        self.closed = true;
+        manager::with_instance(|ctx| ctx.close_collector(self.id))
+    }
+}
+
+impl Drop for CollectorGuard {
+    fn drop(&mut self) {
+        COLLECTORS.with(|c| c.borrow_mut().pop());
+        if !self.closed {
+            manager::with_instance(|ctx| ctx.close_collector(self.id));
+        }
+    }
+}
+
+/// Run `f` with a collector installed on the current thread, returns its result and the collected metrics
+pub(crate) fn scoped_collector<F, R>(f: F) -> (R, Vec<ExecDuration>)
+where
+    F: FnOnce() -> R,
+{
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    manager::with_instance(|ctx| ctx.open_collector(id));
+    COLLECTORS.with(|c| c.borrow_mut().push(id));
+    let guard = CollectorGuard { id, closed: false };
+    let res = f();
+    (res, guard.close())
+}
+
+/// Innermost collector of the current thread, if any
+pub(crate) fn current() -> Option<u64> {
+    COLLECTORS.with(|c| c.borrow().last().copied())
+}
@@ -0,0 +1,66 @@
+use crate::InitError;
+use std::env;
+use std::str::FromStr;
+
+const ENABLED: &str = "EXEC_DURATION_ENABLED";
+const SAMPLE_RATE: &str = "EXEC_DURATION_SAMPLE_RATE";
+const MAX_PROBES: &str = "EXEC_DURATION_MAX_PROBES";
+
+/// Defaults of the metrics collection, read from the environment
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Config {
+    /// Whether executions are collected at all
+    pub enabled: bool,
+    /// Fraction of the executions collected, in [0, 1]
+    pub sample_rate: f64,
+    /// Maximum number of distinct probes, executions of new probes are dropped beyond.
+    /// Only the global in-memory aggregates are capped, not the sessions nor an installed store
+    pub max_probes: Option<usize>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            enabled: true,
+            sample_rate: 1.0,
+            max_probes: None,
+        }
+    }
+}
+
+impl Config {
+    pub(crate) fn from_env() -> Result<Self, InitError> {
+        let mut config = Config::default();
+        if let Some(value) = var(ENABLED)? {
+            config.enabled = match value.to_ascii_lowercase().as_str() {
+                "1" | "true" | "yes" | "on" => true,
+                "0" | "false" | "no" | "off" => false,
+                _ => return Err(InitError::InvalidEnv(ENABLED.to_string())),
+            };
+        }
+        if let Some(value) = var(SAMPLE_RATE)? {
+            config.sample_rate = parse(SAMPLE_RATE, &value)?;
+            if !(0.0..=1.0).contains(&config.sample_rate) {
+                return Err(InitError::InvalidEnv(SAMPLE_RATE.to_string()));
+            }
+        }
+        if let Some(value) = var(MAX_PROBES)? {
+            config.max_probes = Some(parse(MAX_PROBES, &value)?);
+        }
+        Ok(config)
+    }
+}
+
+fn var(name: &str) -> Result<Option<String>, InitError> {
+    match env::var(name) {
+        Ok(value) => Ok(Some(value.trim().to_string())),
+        Err(env::VarError::NotPresent) => Ok(None),
+        Err(env::VarError::NotUnicode(_)) => Err(InitError::InvalidEnv(name.to_string())),
+    }
+}
+
+fn parse<T: FromStr>(name: &str, value: &str) -> Result<T, InitError> {
+    value
+        .parse()
+        .map_err(|_| InitError::InvalidEnv(name.to_string()))
+}
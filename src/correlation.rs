@@ -0,0 +1,42 @@
+use std::cell::RefCell;
+
+thread_local! {
+    static IDS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Scope guard of a correlation id on the current thread.
+/// Instances are created using `exec_duration::set_correlation_id` function, the previous id is restored
+/// when the guard goes out of scope.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ExecProbe;
+///
+/// let _id = exec_duration::set_correlation_id("req-42");
+/// let ep = ExecProbe::new("handler");
+/// assert_eq!(ep.correlation_id(), Some("req-42"));
+/// ```
+#[derive(Debug)]
+#[must_use = "the correlation id is unset as soon as the guard is dropped"]
+pub struct CorrelationGuard {
+    _private: (),
+}
+
+impl CorrelationGuard {
+    pub(crate) fn new(id: &str) -> Self {
+        IDS.with(|ids| ids.borrow_mut().push(id.to_string()));
+        CorrelationGuard { _private: () }
+    }
+}
+
+impl Drop for CorrelationGuard {
+    fn drop(&mut self) {
+        IDS.with(|ids| ids.borrow_mut().pop());
+    }
+}
+
+/// Innermost correlation id of the current thread, if any
+pub(crate) fn current() -> Option<String> {
+    IDS.with(|ids| ids.borrow().last().cloned())
+}
@@ -0,0 +1,59 @@
+use std::cell::Cell;
+
+thread_local! {
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+    static PEAK: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Scope guard tracking the nesting depth of the current thread.
+/// Instances are created using `exec_duration::depth_guard` function, the depth is incremented on creation and
+/// decremented when the guard goes out of scope.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// fn recursive(n: usize) {
+///     let _guard = exec_duration::depth_guard();
+///     if n > 0 {
+///         recursive(n - 1);
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+#[must_use = "the depth is decremented as soon as the guard is dropped"]
+pub struct DepthGuard {
+    _private: (),
+}
+
+impl DepthGuard {
+    pub(crate) fn new() -> Self {
+        let depth = DEPTH.with(|d| {
+            d.set(d.get() + 1);
+            d.get()
+        });
+        PEAK.with(|p| p.set(p.get().max(depth)));
+        DepthGuard { _private: () }
+    }
+}
+
+impl Drop for DepthGuard {
+    fn drop(&mut self) {
+        DEPTH.with(|d| d.set(d.get().saturating_sub(1)));
+    }
+}
+
+/// Start observing the depth, returns the state to give back to `end`
+pub(crate) fn begin() -> usize {
+    let depth = DEPTH.with(|d| d.get());
+    PEAK.with(|p| p.replace(depth))
+}
+
+/// Stop observing the depth, returns the deepest depth observed since `begin`
+pub(crate) fn end(saved: usize) -> usize {
+    PEAK.with(|p| {
+        let observed = p.get();
+        p.set(saved.max(observed));
+        observed
+    })
+}
@@ -0,0 +1,104 @@
+use crate::output::DurationUnit;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use tdigest::TDigest;
+
+/// Number of durations buffered before being merged into the digest
+const BUFFER_SIZE: usize = 512;
+
+/// Distribution of the execution durations of a probe, in bounded memory
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Digest {
+    digest: TDigest,
+    buffer: Vec<f64>,
+}
+
+impl Digest {
+    pub(crate) fn record(&mut self, duration: DurationUnit) {
+        self.buffer.push(duration as f64);
+        if self.buffer.len() >= BUFFER_SIZE {
+            let values = std::mem::take(&mut self.buffer);
+            self.digest = self.digest.merge_unsorted(values);
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> Percentiles {
+        Percentiles(Arc::new(self.digest.merge_unsorted(self.buffer.clone())))
+    }
+}
+
+/// Snapshot of a digest attached to the results.
+/// Being an estimation, it never takes part in comparisons nor in hashing.
+#[derive(Clone)]
+pub(crate) struct Percentiles(Arc<TDigest>);
+
+impl Percentiles {
+    /// Estimate the duration at the given quantile, in [0, 1]
+    pub(crate) fn quantile(&self, q: f64) -> Option<DurationUnit> {
+        if self.0.is_empty() {
+            return None;
+        }
+        Some(self.0.estimate_quantile(q).max(0.0).round() as DurationUnit)
+    }
+
+    pub(crate) fn merge(&self, other: &Percentiles) -> Percentiles {
+        let digests = vec![(*self.0).clone(), (*other.0).clone()];
+        Percentiles(Arc::new(TDigest::merge_digests(digests)))
+    }
+}
+
+impl fmt::Debug for Percentiles {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Percentiles")
+    }
+}
+
+impl PartialEq for Percentiles {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for Percentiles {}
+
+impl PartialOrd for Percentiles {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Percentiles {
+    fn cmp(&self, _: &Self) -> Ordering {
+        Ordering::Equal
+    }
+}
+
+impl Hash for Percentiles {
+    fn hash<H: Hasher>(&self, _: &mut H) {}
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::digest::Digest;
+
+    #[test]
+    fn uniform_distribution() {
+        let mut digest = Digest::default();
+        for d in 1..=100_000 {
+            digest.record(d);
+        }
+        let p = digest.snapshot();
+        let p50 = p.quantile(0.5).unwrap() as f64;
+        let p99 = p.quantile(0.99).unwrap() as f64;
+        assert!((p50 - 50_000.0).abs() < 500.0, "p50 = {}", p50);
+        assert!((p99 - 99_000.0).abs() < 200.0, "p99 = {}", p99);
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(Digest::default().snapshot().quantile(0.5), None);
+    }
+}
@@ -0,0 +1,95 @@
+use std::error::Error;
+use std::fmt;
+
+/// Error returned by `exec_duration::init`
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// if let Err(e) = exec_duration::init() {
+///     eprintln!("metrics collection is not available: {}", e);
+/// }
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum InitError {
+    /// A panic occurred while the metrics were being updated, for instance in a user callback
+    Poisoned,
+    /// The named environment variable holds an invalid value
+    InvalidEnv(String),
+}
+
+impl fmt::Display for InitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InitError::Poisoned => write!(f, "metrics manager poisoned by a panic"),
+            InitError::InvalidEnv(name) => {
+                write!(f, "invalid value of environment variable {}", name)
+            }
+        }
+    }
+}
+
+impl Error for InitError {}
+
+/// Error returned when execution metrics or their configuration do not hold the expected invariants
+///
+/// # Examples
+/// ```
+/// use exec_duration::output::ExecDuration;
+/// use exec_duration::ValidationError;
+///
+/// let err = ExecDuration::builder("main").build().unwrap_err();
+/// assert_eq!(err, ValidationError::ZeroCount(String::from("main")));
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum ValidationError {
+    /// The named node has no execution
+    ZeroCount(String),
+    /// Declaring the named alias would make a cycle of aliases
+    AliasCycle(String),
+    /// The named node has more zero-duration or provisional executions than executions
+    CountMismatch(String),
+    /// The named node has a shortest or longest execution time out of its range
+    RangeMismatch(String),
+    /// The named element lasts longer than its probe
+    ChildExceedsParent(String),
+    /// The named element has a reference total different from the duration of its probe
+    TotalMismatch(String),
+    /// The named unit is not supported by the metrics exporter
+    UnknownUnit(String),
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::ZeroCount(name) => write!(f, "[{}] has no execution", name),
+            ValidationError::AliasCycle(name) => write!(f, "[{}] alias makes a cycle", name),
+            ValidationError::CountMismatch(name) => {
+                write!(
+                    f,
+                    "[{}] has more sub-counted executions than executions",
+                    name
+                )
+            }
+            ValidationError::RangeMismatch(name) => {
+                write!(f, "[{}] has min/max execution times out of range", name)
+            }
+            ValidationError::ChildExceedsParent(name) => {
+                write!(f, "[{}] lasts longer than its parent", name)
+            }
+            ValidationError::TotalMismatch(name) => {
+                write!(
+                    f,
+                    "[{}] reference total differs from its parent duration",
+                    name
+                )
+            }
+            ValidationError::UnknownUnit(name) => write!(f, "[{}] is not a supported unit", name),
+        }
+    }
+}
+
+impl Error for ValidationError {}
@@ -0,0 +1,139 @@
+use crate::output::{DurationUnit, ExecDuration};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Default)]
+pub(crate) struct FastCounter {
+    name: String,
+    count: AtomicU64,
+    duration: AtomicU64,
+}
+
+impl FastCounter {
+    pub(crate) fn new(name: &str) -> Self {
+        FastCounter {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub(crate) fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn duration(&self) -> DurationUnit {
+        self.duration.load(Ordering::Relaxed) as DurationUnit
+    }
+
+    pub(crate) fn to_exec_duration(&self) -> Option<ExecDuration> {
+        let count = self.count.load(Ordering::Relaxed);
+        let duration = self.duration.load(Ordering::Relaxed) as DurationUnit;
+        if count == 0 {
+            return None;
+        }
+        Some(ExecDuration::new(&self.name, count, duration, duration))
+    }
+
+    /// Take the aggregates, resetting the counters
+    pub(crate) fn drain(&self) -> Option<ExecDuration> {
+        let count = self.count.swap(0, Ordering::Relaxed);
+        let duration = self.duration.swap(0, Ordering::Relaxed) as DurationUnit;
+        if count == 0 {
+            return None;
+        }
+        Some(ExecDuration::new(&self.name, count, duration, duration))
+    }
+
+    fn record(&self, duration: Duration) {
+        self.duration
+            .fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Handle on a probe aggregated with atomic counters.
+/// Instances are created using `exec_duration::register_fast` function.
+///
+/// Reporting through a handle never takes a lock nor looks up a map, which makes it suitable for the hottest
+/// code paths. Only the execution count and the total duration (up to ~584 years) are aggregated, points are not
+/// supported.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// let handle = exec_duration::register_fast("hot_function");
+/// {
+///     let _probe = handle.start();
+///     // code
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FastProbeHandle {
+    counter: Arc<FastCounter>,
+}
+
+impl FastProbeHandle {
+    pub(crate) fn new(counter: Arc<FastCounter>) -> Self {
+        FastProbeHandle { counter }
+    }
+
+    /// Start measuring an execution, the elapsed time is reported when the returned probe goes out of scope
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    ///
+    /// let handle = exec_duration::register_fast("hot_function");
+    /// let probe = handle.start();
+    /// // code
+    /// drop(probe);
+    /// ```
+    pub fn start(&self) -> FastProbe<'_> {
+        FastProbe {
+            counter: &self.counter,
+            begin: Instant::now(),
+        }
+    }
+
+    /// Report an execution measured elsewhere
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use std::time::Duration;
+    ///
+    /// let handle = exec_duration::register_fast("hot_function");
+    /// handle.record(Duration::from_micros(3));
+    /// ```
+    pub fn record(&self, duration: Duration) {
+        self.counter.record(duration);
+    }
+}
+
+/// Execution measured through a `FastProbeHandle`, reported on drop.
+/// Instances are created using `FastProbeHandle::start` function.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// let handle = exec_duration::register_fast("hot_function");
+/// let _probe = handle.start();
+/// ```
+#[derive(Debug)]
+pub struct FastProbe<'a> {
+    counter: &'a FastCounter,
+    begin: Instant,
+}
+
+impl Drop for FastProbe<'_> {
+    fn drop(&mut self) {
+        self.counter.record(self.begin.elapsed());
+    }
+}
@@ -0,0 +1,66 @@
+use crate::Token;
+use rustc_hash::FxHashMap as HashMap;
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+/// Measurements started by C callers, by handle
+static HANDLES: Mutex<Option<HashMap<u64, Token>>> = Mutex::new(None);
+/// Handles start at 1, 0 reports an invalid name
+static NEXT_HANDLE: AtomicU64 = AtomicU64::new(1);
+
+fn lock() -> MutexGuard<'static, Option<HashMap<u64, Token>>> {
+    HANDLES.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Start measuring an execution of the probe `name` and return the handle to give to `exec_duration_stop`.
+/// Returns 0, and measures nothing, if `name` is null or is not valid UTF-8.
+///
+/// # Safety
+/// `name` must be null or point to a nul-terminated string valid for the duration of the call.
+///
+/// # Examples
+/// ```
+/// use exec_duration::ffi;
+/// use std::ffi::CString;
+///
+/// let name = CString::new("callback").unwrap();
+/// let handle = unsafe { ffi::exec_duration_start(name.as_ptr()) };
+/// ffi::exec_duration_stop(handle);
+/// ```
+#[no_mangle]
+pub unsafe extern "C" fn exec_duration_start(name: *const c_char) -> u64 {
+    if name.is_null() {
+        return 0;
+    }
+    let name = match CStr::from_ptr(name).to_str() {
+        Ok(name) => name,
+        Err(_) => return 0,
+    };
+    let handle = NEXT_HANDLE.fetch_add(1, Ordering::Relaxed);
+    lock()
+        .get_or_insert_with(HashMap::default)
+        .insert(handle, crate::start(name));
+    handle
+}
+
+/// Report the execution started by `exec_duration_start`.
+/// Unknown handles, including 0 and handles already stopped, are ignored.
+///
+/// # Examples
+/// ```
+/// use exec_duration::ffi;
+/// use std::ffi::CString;
+///
+/// let name = CString::new("callback").unwrap();
+/// let handle = unsafe { ffi::exec_duration_start(name.as_ptr()) };
+/// ffi::exec_duration_stop(handle);
+/// ```
+#[no_mangle]
+pub extern "C" fn exec_duration_stop(handle: u64) {
+    let token = lock().as_mut().and_then(|h| h.remove(&handle));
+    if let Some(token) = token {
+        crate::finish(token);
+    }
+}
@@ -0,0 +1,23 @@
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+thread_local! {
+    static LAST_STOP: Cell<Option<Instant>> = const { Cell::new(None) };
+    static LAST_GAP: Cell<Option<Duration>> = const { Cell::new(None) };
+}
+
+/// A probe started on the current thread
+pub(crate) fn started() {
+    if let Some(stop) = LAST_STOP.with(|s| s.get()) {
+        LAST_GAP.with(|g| g.set(Some(stop.elapsed())));
+    }
+}
+
+/// A probe stopped on the current thread
+pub(crate) fn stopped() {
+    LAST_STOP.with(|s| s.set(Some(Instant::now())));
+}
+
+pub(crate) fn last() -> Option<Duration> {
+    LAST_GAP.with(|g| g.get())
+}
@@ -0,0 +1,79 @@
+use crate::output::DurationUnit;
+
+/// Execution counts per duration bucket.
+/// Bucket `i` covers `[bounds[i - 1], bounds[i])`, the first one starts at zero and the last one is unbounded.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub(crate) struct Histogram {
+    bounds: Vec<DurationUnit>,
+    counts: Vec<u64>,
+}
+
+impl Histogram {
+    /// `bounds` are the ascending upper bounds of the buckets, an extra bucket holds the durations beyond the last one
+    pub(crate) fn new(bounds: &[DurationUnit]) -> Self {
+        Histogram {
+            bounds: bounds.to_vec(),
+            counts: vec![0; bounds.len() + 1],
+        }
+    }
+
+    pub(crate) fn record(&mut self, duration: DurationUnit) {
+        let index = self.bounds.partition_point(|b| *b <= duration);
+        self.counts[index] += 1;
+    }
+
+    /// Merge the counts of another histogram, only when both have the same buckets
+    pub(crate) fn merge(&mut self, other: &Histogram) -> bool {
+        if self.bounds != other.bounds {
+            return false;
+        }
+        for (count, other) in self.counts.iter_mut().zip(other.counts.iter()) {
+            *count += other;
+        }
+        true
+    }
+
+    /// Same counts with the bucket bounds mapped by `scale`
+    pub(crate) fn scaled(&self, scale: impl Fn(DurationUnit) -> DurationUnit) -> Histogram {
+        Histogram {
+            bounds: self.bounds.iter().map(|b| scale(*b)).collect(),
+            counts: self.counts.clone(),
+        }
+    }
+
+    /// Range of the bucket with the highest count, the lowest one on ties, `None` when empty
+    pub(crate) fn mode(&self) -> Option<(DurationUnit, Option<DurationUnit>)> {
+        let (index, count) = self
+            .counts
+            .iter()
+            .enumerate()
+            .max_by(|(i, a), (j, b)| a.cmp(b).then(j.cmp(i)))?;
+        if *count == 0 {
+            return None;
+        }
+        let lower = if index == 0 {
+            0
+        } else {
+            self.bounds[index - 1]
+        };
+        Some((lower, self.bounds.get(index).copied()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::histogram::Histogram;
+
+    #[test]
+    fn buckets() {
+        let mut h = Histogram::new(&[10, 100]);
+        for d in [0, 9, 10, 99, 100, 1000, 1000] {
+            h.record(d);
+        }
+        assert_eq!(h.counts, vec![2, 2, 3]);
+        assert_eq!(h.mode(), Some((100, None)));
+        assert_eq!(Histogram::new(&[10]).mode(), None);
+    }
+}
@@ -63,7 +63,7 @@
 
 #![doc(issue_tracker_base_url = "https://github.com/adlane/exec_duration/issues/")]
 #![doc(html_root_url = "https://docs.rs/exec_duration/0.1.1")]
-#![doc(html_no_source)]
+#![cfg_attr(docsrs, doc(html_no_source))]
 #![deny(missing_docs)]
 
 #[cfg(feature = "serde")]
@@ -74,9 +74,165 @@ extern crate serde;
 #[macro_use]
 extern crate more_asserts;
 
+mod aggregator;
+#[cfg(feature = "criterion")]
+mod bench;
+mod builder;
+mod call_tree;
+mod clock;
+mod collector;
+mod config;
+mod correlation;
+mod depth;
+#[cfg(feature = "tdigest")]
+mod digest;
+mod error;
+mod fast;
+/// C interface, for non-Rust code feeding the same metrics
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod gap;
+mod histogram;
+mod live;
+mod lock;
+#[cfg(feature = "log")]
+mod logged;
 mod manager;
+mod marker;
+mod measure;
+#[cfg(feature = "otel")]
+mod otel;
 /// output module exposes the results (metrics)
 pub mod output;
+mod overhead;
+#[cfg(feature = "prometheus")]
+mod prometheus;
+mod replay;
+mod reservoir;
+mod session;
+#[cfg(feature = "statsd")]
+mod statsd;
+mod store;
+mod swap;
+mod timed;
+mod timer;
+mod trace;
+mod window;
+
+pub use aggregator::CustomAgg;
+pub use builder::ExecProbeBuilder;
+pub use clock::{Clock, ClockAnomalyPolicy};
+pub use correlation::CorrelationGuard;
+pub use depth::DepthGuard;
+pub use error::{InitError, ValidationError};
+/// Derive macro implementing `Timed`, the probe is named after the type
+#[cfg(feature = "derive")]
+pub use exec_duration_derive::Timed;
+pub use fast::{FastProbe, FastProbeHandle};
+pub use lock::LockTimer;
+#[cfg(feature = "log")]
+pub use logged::LoggedMeasure;
+pub use manager::Point;
+pub use measure::AsyncMeasure;
+pub use replay::RawEvent;
+pub use store::Store;
+pub use swap::ExecProbeManager;
+pub use timed::Timed;
+pub use timer::SubTimer;
+pub use trace::TraceContext;
+
+use std::sync::{Arc, LockResult, Mutex};
+use std::time::{Duration, Instant};
+
+/// Initialize the metrics collection.
+/// Calling this function is optional, the collection is lazily initialized on first use otherwise,
+/// but it gives a clear point to verify that the subsystem is ready. Calling it more than once has no effect.
+///
+/// The defaults of the collection are read from the following environment variables on initialization:
+/// - `EXEC_DURATION_ENABLED`: `0`, `false`, `no` or `off` disables the collection
+/// - `EXEC_DURATION_SAMPLE_RATE`: fraction of the executions collected, between 0 and 1
+/// - `EXEC_DURATION_MAX_PROBES`: maximum number of distinct probes, executions of new probes are dropped beyond
+///
+/// This function fails with `InitError::InvalidEnv` if one of them holds an invalid value,
+/// whereas invalid values are ignored by the lazy initialization.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// exec_duration::init().expect("metrics collection is not available");
+///
+/// // code
+/// ```
+pub fn init() -> Result<(), InitError> {
+    manager::init()
+}
+
+/// Free the metrics collection, along with all the collected metrics and registered callbacks.
+/// The collection is initialized again on next use, which makes it suitable for plugins that get unloaded.
+///
+/// It is a logic error to call this function while probes are alive: they would report into the next collection,
+/// and the handles returned by `register_fast` would no longer be part of the results.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ExecProbe;
+///
+/// {
+///     let mut ep = ExecProbe::new("main");
+///     ep.add_point("line 1");
+/// }
+/// exec_duration::shutdown();
+/// assert!(exec_duration::fetch_results().is_empty());
+/// ```
+pub fn shutdown() {
+    manager::shutdown()
+}
+
+/// Replace the global collection with `new` and return the previous one, along with all its metrics.
+/// The lock is only held for the exchange, so the previous metrics can be processed at leisure
+/// while new executions are reported into `new`.
+///
+/// The settings and callbacks are part of the collection and are exchanged as well. Probes alive during the
+/// exchange report into `new`, the handles returned by `register_fast` keep counting into the previous collection.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::{ExecProbe, ExecProbeManager};
+///
+/// {
+///     let mut ep = ExecProbe::new("main");
+///     ep.add_point("line 1");
+/// }
+/// let previous = exec_duration::swap(ExecProbeManager::new());
+/// assert_eq!(previous.fetch_results().len(), 1);
+/// assert!(!exec_duration::has_data());
+/// ```
+pub fn swap(new: ExecProbeManager) -> ExecProbeManager {
+    swap::swap(new)
+}
+
+/// Merge executions aggregated elsewhere as `(name, count, total duration)` entries, under a single lock.
+/// Each entry adds `count` executions of the probe `name` lasting `total duration` altogether, entries without
+/// execution are ignored. The imported executions have no element, and the statistics depending on individual
+/// durations (min, max, coefficient of variation) assume that they all last the average of their entry.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use std::time::Duration;
+///
+/// exec_duration::bulk_import(&[
+///     (String::from("remote_call"), 10, Duration::from_millis(250)),
+///     (String::from("remote_parse"), 10, Duration::from_millis(30)),
+/// ]);
+/// assert_eq!(exec_duration::fetch_results().len(), 2);
+/// ```
+pub fn bulk_import(entries: &[(String, u64, Duration)]) {
+    manager::with_instance(|ctx| ctx.bulk_import(entries))
+}
 
 /// Fetch execution metrics.
 /// Typically, this function needs to be called once the execution of all measured blocks is done.
@@ -90,18 +246,1235 @@ pub mod output;
 ///
 /// // code
 ///
-/// // fetch results
-/// let list = exec_duration::fetch_results();
-/// for r in list.iter() {
+/// // fetch results
+/// let list = exec_duration::fetch_results();
+/// for r in list.iter() {
+///     println!("{}", r);
+/// }
+/// ```
+pub fn fetch_results() -> Vec<output::ExecDuration> {
+    manager::with_instance(|ctx| {
+        ctx.abandon_overdue();
+        if ctx.auto_drain() {
+            ctx.drain_results()
+        } else {
+            ctx.fetch_results()
+        }
+    })
+}
+
+/// Fetch execution metrics without draining them, even in auto-drain mode.
+/// Used by the helpers built on top of the results, so that only `fetch_results` and `fetch_results_into` drain.
+fn snapshot() -> Vec<output::ExecDuration> {
+    manager::with_instance(|ctx| {
+        ctx.abandon_overdue();
+        ctx.fetch_results()
+    })
+}
+
+/// Fetch execution metrics into the given buffer, cleared and refilled.
+/// Reusing the same buffer across calls reuses its capacity, avoiding a fresh allocation of the list on every poll.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// let mut buf = Vec::new();
+/// loop {
+///     exec_duration::fetch_results_into(&mut buf);
+///     for r in buf.iter() {
+///         println!("{}", r);
+///     }
+///     # break;
+/// }
+/// ```
+pub fn fetch_results_into(buf: &mut Vec<output::ExecDuration>) {
+    manager::with_instance(|ctx| {
+        ctx.abandon_overdue();
+        if ctx.auto_drain() {
+            ctx.drain_results_into(buf)
+        } else {
+            ctx.fetch_results_into(buf)
+        }
+    })
+}
+
+/// Register a callback invoked each time an execution is reported,
+/// with the probe name, the execution total duration and the points of that single execution.
+/// Callbacks are invoked in registration order, once the collection is unlocked: they may create probes or fetch
+/// the results, bearing in mind that the probes reported from a callback invoke the callbacks again.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::{ExecProbe, Point};
+/// use std::time::Duration;
+///
+/// exec_duration::on_report(|name: &str, total: Duration, points: &[Point]| {
+///     println!("[{}] costs {:?} ({} points)", name, total, points.len());
+/// });
+///
+/// let mut ep = ExecProbe::new("main");
+/// ep.add_point("line 1");
+/// ```
+pub fn on_report<F>(cb: F)
+where
+    F: Fn(&str, Duration, &[Point]) + Send + Sync + 'static,
+{
+    manager::with_instance(|ctx| {
+        ctx.on_report(Arc::new(move |name, total, points, _| {
+            cb(name, total, points)
+        }))
+    });
+}
+
+/// Register a callback invoked each time an execution is reported, as `on_report` does,
+/// additionally given the tracing context of the execution if it has one (see `ExecProbe::with_trace_context`).
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::{ExecProbe, Point, TraceContext};
+/// use std::time::Duration;
+///
+/// exec_duration::on_report_traced(|name: &str, total: Duration, _: &[Point], ctx: Option<&TraceContext>| {
+///     if let Some(ctx) = ctx {
+///         println!("[{}] costs {:?} (trace {:032x})", name, total, ctx.trace_id);
+///     }
+/// });
+/// ```
+pub fn on_report_traced<F>(cb: F)
+where
+    F: Fn(&str, Duration, &[Point], Option<&TraceContext>) + Send + Sync + 'static,
+{
+    manager::with_instance(|ctx| ctx.on_report(Arc::new(cb)));
+}
+
+/// Increment the nesting depth of the current thread until the returned guard goes out of scope.
+/// The deepest depth reached while a probe is alive is exposed by `ExecDuration::get_max_depth`.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ExecProbe;
+///
+/// fn recursive(n: usize) {
+///     let _guard = exec_duration::depth_guard();
+///     if n > 0 {
+///         recursive(n - 1);
+///     }
+/// }
+///
+/// let mut ep = ExecProbe::new("main");
+/// recursive(10);
+/// ep.add_point("recursive");
+/// ```
+pub fn depth_guard() -> DepthGuard {
+    DepthGuard::new()
+}
+
+/// Record a named timestamp on the current thread, to time a span whose bounds are not probe boundaries
+/// (across functions, callbacks, ...) using `between`. Marking a name again replaces its timestamp.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// exec_duration::mark("request received");
+/// // code
+/// exec_duration::mark("response sent");
+/// ```
+pub fn mark(name: &str) {
+    marker::mark(name)
+}
+
+/// Get the time elapsed between the marks `a` and `b` recorded by `mark` on the current thread.
+/// Returns `None` if one of them is missing, or if `b` was marked before `a`.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// exec_duration::mark("request received");
+/// // code
+/// exec_duration::mark("response sent");
+/// if let Some(d) = exec_duration::between("request received", "response sent") {
+///     println!("handled in {:?}", d);
+/// }
+/// ```
+pub fn between(a: &str, b: &str) -> Option<Duration> {
+    marker::between(a, b)
+}
+
+/// Set the correlation id of the current thread until the returned guard goes out of scope, the previous one is
+/// restored then. The probes created meanwhile on the thread carry the id, see `ExecProbe::correlation_id`,
+/// which ties the nested probes of a request together. Only the probes bound to a thread are considered,
+/// `measure_async` and `start` are not.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ExecProbe;
+///
+/// fn query() {
+///     let mut ep = ExecProbe::new("query");
+///     assert_eq!(ep.correlation_id(), Some("req-42"));
+///     ep.add_point("line 1");
+/// }
+///
+/// let _id = exec_duration::set_correlation_id("req-42");
+/// let mut ep = ExecProbe::new("handler");
+/// query();
+/// ep.add_point("query");
+/// ```
+pub fn set_correlation_id(id: &str) -> CorrelationGuard {
+    CorrelationGuard::new(id)
+}
+
+/// Get the idle time of the current thread before the last probe started, that is the time elapsed between the
+/// end of the previous probe and the start of the last one. Returns `None` until a probe is started after another
+/// one ended on the current thread. Only the probes bound to a thread are considered, `measure_async` and `start`
+/// are not.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ExecProbe;
+///
+/// let mut ep = ExecProbe::new("first");
+/// ep.stop();
+/// // idle
+/// let mut ep = ExecProbe::new("second");
+/// println!("idle for {:?}", exec_duration::last_gap().unwrap());
+/// ```
+pub fn last_gap() -> Option<Duration> {
+    gap::last()
+}
+
+/// Start measuring an execution whose end is not bound to a lexical scope.
+/// The returned token must be given back to `finish` to report the elapsed time.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// let token = exec_duration::start("callback");
+///
+/// // code
+///
+/// exec_duration::finish(token);
+/// ```
+pub fn start(name: &str) -> Token {
+    Token {
+        name: name.to_string(),
+        begin: Instant::now(),
+    }
+}
+
+/// Start measuring an execution which is both reported and logged, at the given level, when the returned guard
+/// goes out of scope. The log record is emitted with the `exec_duration` target and holds the elapsed time.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use log::Level;
+///
+/// fn load_config() {
+///     let _m = exec_duration::measure_logged("load_config", Level::Debug);
+///     // code
+/// }
+/// ```
+#[cfg(feature = "log")]
+pub fn measure_logged(name: &str, level: log::Level) -> LoggedMeasure {
+    LoggedMeasure::new(name, level)
+}
+
+/// Enter a call on the call stack of the current thread, until the matching `exit`.
+/// The time of a call is attributed under the call active when it was entered, so that `fetch_results` holds
+/// the whole call tree: the outermost calls with their callees as elements, recursively.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// fn parse() {
+///     exec_duration::enter("parse");
+///     // code
+///     exec_duration::exit();
+/// }
+///
+/// exec_duration::enter("main");
+/// parse();
+/// exec_duration::exit();
+/// ```
+pub fn enter(name: &str) {
+    call_tree::enter(name)
+}
+
+/// Exit the innermost call entered with `enter` on the current thread, this is a no-op if there is none
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// exec_duration::enter("main");
+/// // code
+/// exec_duration::exit();
+/// ```
+pub fn exit() {
+    call_tree::exit()
+}
+
+/// Start measuring an async execution, the wall time elapsed is reported when the returned guard goes out of scope.
+/// The guard is `Send` and can be held across await points, points are added using `AsyncMeasure::checkpoint`.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// async fn handler() {
+///     let mut m = exec_duration::measure_async("handler");
+///     // read().await;
+///     m.checkpoint("read");
+/// }
+/// ```
+pub fn measure_async(name: &str) -> AsyncMeasure {
+    AsyncMeasure::new(name)
+}
+
+/// Report the time elapsed since the given token was created by `start`.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// let token = exec_duration::start("callback");
+///
+/// // code
+///
+/// exec_duration::finish(token);
+/// ```
+pub fn finish(token: Token) {
+    let mut data = manager::ExecData::detached(&token.name);
+    data.record_points = false;
+    data.stop_with_duration(token.begin.elapsed().as_nanos());
+}
+
+/// Run `f` and return its value along with its wall duration.
+/// Nothing is reported: unlike probes, this function has no side effect on the collected metrics,
+/// which makes it suitable for library code.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// let (sum, duration) = exec_duration::time_closure(|| (0..1000u64).sum::<u64>());
+/// println!("sum {} computed in {:?}", sum, duration);
+/// ```
+pub fn time_closure<T, F>(f: F) -> (T, Duration)
+where
+    F: FnOnce() -> T,
+{
+    let begin = Instant::now();
+    let value = f();
+    (value, begin.elapsed())
+}
+
+/// Run `f` until it succeeds, at most `attempts` times (at least once), and measure the whole as an execution of
+/// the probe `name`. Each attempt is recorded as a point named after its number, `attempt_0`, `attempt_1`, ...
+/// and given to `f`. Returns the first success, or the error of the last attempt.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// let res: Result<u32, &str> = exec_duration::timed_retry("connect", 3, |attempt| {
+///     if attempt < 2 {
+///         Err("unreachable")
+///     } else {
+///         Ok(attempt)
+///     }
+/// });
+/// assert_eq!(res, Ok(2));
+/// ```
+pub fn timed_retry<T, E, F>(name: &str, attempts: u32, mut f: F) -> Result<T, E>
+where
+    F: FnMut(u32) -> Result<T, E>,
+{
+    let mut ep = ExecProbe::new(name);
+    let mut attempt = 0;
+    loop {
+        let res = f(attempt);
+        ep.add_point(&format!("attempt_{}", attempt));
+        attempt += 1;
+        if res.is_ok() || attempt >= attempts {
+            return res;
+        }
+    }
+}
+
+/// Acquire the mutex and measure how long it is held.
+/// The hold duration is reported as an execution of the probe `name` when the returned guard goes out of scope,
+/// poisoning is reported the same way as `Mutex::lock`.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use std::sync::Mutex;
+///
+/// let mutex = Mutex::new(Vec::new());
+/// exec_duration::timed_lock(&mutex, "queue_lock").unwrap().push(1);
+/// ```
+pub fn timed_lock<'a, T: ?Sized>(mutex: &'a Mutex<T>, name: &str) -> LockResult<LockTimer<'a, T>> {
+    lock::timed_lock(mutex, name)
+}
+
+/// Convert execution metrics to values of criterion's wall time measurement, one per probe.
+/// The value of a probe is its total execution time, as expected by `Bencher::iter_custom`
+/// for the number of iterations the probe was executed.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ExecProbe;
+///
+/// let mut ep = ExecProbe::new("main");
+///
+/// // code
+///
+/// let list = exec_duration::fetch_results();
+/// for (name, value) in exec_duration::to_criterion_measurements(&list) {
+///     println!("[{}] {:?}", name, value);
+/// }
+/// ```
+#[cfg(feature = "criterion")]
+pub fn to_criterion_measurements(
+    results: &[output::ExecDuration],
+) -> Vec<(
+    String,
+    <criterion::measurement::WallTime as criterion::measurement::Measurement>::Value,
+)> {
+    bench::to_measurements(results)
+}
+
+/// Export execution metrics to OpenTelemetry.
+/// A histogram (in seconds) is registered per probe and records the probe total duration,
+/// each point total duration is recorded in the same histogram with a `point` attribute.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ExecProbe;
+///
+/// let mut ep = ExecProbe::new("main");
+///
+/// // code
+///
+/// let meter = opentelemetry::global::meter("exec_duration");
+/// exec_duration::export_to_otel(&meter);
+/// ```
+#[cfg(feature = "otel")]
+pub fn export_to_otel(meter: &opentelemetry::metrics::Meter) {
+    otel::export(meter, &snapshot());
+}
+
+/// Export execution metrics to a StatsD server over UDP.
+/// A `timing` metric (in milliseconds) is sent per probe with its total duration, and per point as
+/// `<probe>.<point>`. Sending is best effort: an unreachable or invalid endpoint drops the metrics.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ExecProbe;
+///
+/// let mut ep = ExecProbe::new("main");
+///
+/// // code
+///
+/// exec_duration::export_to_statsd("127.0.0.1:8125");
+/// ```
+#[cfg(feature = "statsd")]
+pub fn export_to_statsd(addr: &str) {
+    statsd::export(addr, &snapshot());
+}
+
+/// Describe the metric family exported for the named probe by `export_to_prometheus`:
+/// its `# HELP` text and the unit of its durations, one of `seconds`, `milliseconds`, `microseconds` or
+/// `nanoseconds`. Describing a probe again replaces its descriptor, probes may be described before they report.
+/// Fails with `ValidationError::UnknownUnit` if the unit is not supported.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// exec_duration::describe("http_request", "Handling of an HTTP request", "milliseconds").unwrap();
+/// ```
+#[cfg(feature = "prometheus")]
+pub fn describe(name: &str, help: &str, unit: &str) -> Result<(), ValidationError> {
+    let descriptor = prometheus::Descriptor::new(help, unit)?;
+    manager::with_instance(|ctx| ctx.describe(name, descriptor));
+    Ok(())
+}
+
+/// Render execution metrics in the Prometheus text exposition format, to be served on a scrape endpoint.
+/// Each probe is a summary family named `<probe>_<unit>` with `_sum` and `_count` series per label set,
+/// the points are series of the same family with a `point` label.
+/// Described probes (see `describe`) get their help text along with `# TYPE` and `# UNIT` lines,
+/// the others are exported in seconds with a generic help text.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ExecProbe;
+///
+/// let mut ep = ExecProbe::new("main");
+///
+/// // code
+///
+/// print!("{}", exec_duration::export_to_prometheus());
+/// ```
+#[cfg(feature = "prometheus")]
+pub fn export_to_prometheus() -> String {
+    let results = snapshot();
+    manager::with_instance(|ctx| prometheus::export(&results, ctx.descriptors()))
+}
+
+/// Report the elapsed time so far of the probes alive as provisional executions, without stopping them.
+/// Provisional executions are part of the results of `fetch_results` until the next call to this function,
+/// or until their probe is stopped, which reports the actual execution instead. See `ExecDuration::get_provisional_count`.
+/// Only the probes created while live tracking is enabled are reported, see `set_live_tracking`.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ExecProbe;
+///
+/// exec_duration::set_live_tracking(true);
+/// let mut ep = ExecProbe::new("event_loop");
+///
+/// // periodic snapshot
+/// exec_duration::flush_live();
+/// for r in exec_duration::fetch_results().iter() {
+///     println!("{}", r);
+/// }
+/// ```
+pub fn flush_live() {
+    manager::with_instance(|ctx| ctx.flush_live())
+}
+
+/// Enable or disable the registration of the probes alive, required by `flush_live` (disabled by default).
+/// Registering a probe takes a global lock on its creation and its end, which contends on hot multi-threaded paths,
+/// so only the probes created while tracking is enabled are registered. Probes with a deadline are always registered.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// exec_duration::set_live_tracking(true);
+/// ```
+pub fn set_live_tracking(enabled: bool) {
+    live::set_tracking(enabled)
+}
+
+/// Print a summary of execution metrics on the standard output, see `output::write_summary_colored`.
+/// Rows are colored by percentage only when the standard output is a terminal.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ExecProbe;
+///
+/// let mut ep = ExecProbe::new("main");
+///
+/// // code
+///
+/// exec_duration::print_summary_colored();
+/// ```
+#[cfg(feature = "color")]
+pub fn print_summary_colored() {
+    use std::io::IsTerminal;
+
+    let stdout = std::io::stdout();
+    let color = stdout.is_terminal();
+    let _ = output::write_summary_colored(&mut stdout.lock(), &snapshot(), color);
+}
+
+/// Fetch the execution metrics of the series whose labels include all the given `(key, value)` pairs.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ExecProbe;
+///
+/// let mut ep = ExecProbe::builder("http_request").label("method", "GET").build();
+///
+/// // code
+///
+/// // fetch results
+/// let list = exec_duration::fetch_results_where(&[("method", "GET")]);
+/// for r in list.iter() {
+///     println!("{}", r);
+/// }
+/// ```
+pub fn fetch_results_where(labels: &[(&str, &str)]) -> Vec<output::ExecDuration> {
+    let mut list = snapshot();
+    list.retain(|r| {
+        labels
+            .iter()
+            .all(|(k, v)| r.get_labels().get(*k).map(|l| l.as_str()) == Some(*v))
+    });
+    list
+}
+
+/// Fetch the execution metrics of the probes whose share of the grand total, across all probes, exceeds
+/// `min_percent`, in descending order of total duration.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// // everything over 5% of the total time
+/// for r in exec_duration::fetch_hotspots(5.0).iter() {
+///     println!("{}", r);
+/// }
+/// ```
+pub fn fetch_hotspots(min_percent: f64) -> Vec<output::ExecDuration> {
+    let mut list = snapshot();
+    let total: Duration = list.iter().map(|r| r.get_total_duration()).sum();
+    if total.is_zero() {
+        return Vec::new();
+    }
+    list.retain(|r| {
+        r.get_total_duration().as_nanos() as f64 * 100.0 / total.as_nanos() as f64 > min_percent
+    });
+    list.sort_by_key(|r| std::cmp::Reverse(r.get_total_duration()));
+    list
+}
+
+/// Fetch execution metrics suitable for snapshot tests, sorted by name and labels,
+/// with the timing dependent values stripped by `ExecDuration::normalized`.
+/// Only the structure and the counts remain, which are stable across runs of a deterministic workload.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ExecProbe;
+///
+/// {
+///     let mut ep = ExecProbe::new("main");
+///     ep.add_point("line 1");
+/// }
+/// let list = exec_duration::fetch_results_normalized();
+/// assert!(list[0].get_total_duration().is_zero());
+/// ```
+pub fn fetch_results_normalized() -> Vec<output::ExecDuration> {
+    let mut list: Vec<output::ExecDuration> = snapshot().iter().map(|r| r.normalized()).collect();
+    list.sort_by(|a, b| (a.get_name(), a.get_labels()).cmp(&(b.get_name(), b.get_labels())));
+    list
+}
+
+/// Fetch the execution metrics of at most `n` probes, in descending order of the given key.
+/// This is cheaper than sorting the whole results because only the selected probes are built.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ExecProbe;
+/// use exec_duration::output::SortKey;
+///
+/// let mut ep = ExecProbe::new("main");
+///
+/// // code
+///
+/// // fetch the 3 slowest probes
+/// let list = exec_duration::fetch_top(3, SortKey::TotalDuration);
+/// for r in list.iter() {
+///     println!("{}", r);
+/// }
+/// ```
+pub fn fetch_top(n: usize, by: output::SortKey) -> Vec<output::ExecDuration> {
+    manager::with_instance(|ctx| ctx.fetch_top(n, by))
+}
+
+/// Reset the execution counts of all probes and their elements, to report the executions since the last reset.
+/// Everything else is kept: the durations, the duration statistics, the exponential moving average
+/// (see `set_ema_alpha`) and the recent activity of the rolling window (see `fetch_results_window`),
+/// which remain the way to get meaningful averages.
+///
+/// The totals still cover all the executions while the counts only cover the ones since the reset: the averages
+/// computed from both, such as `ExecDuration::get_avg_duration`, are meaningless until the next drain, and
+/// `ExecDuration::validate` rejects a probe with a duration but no execution. Fast probes are not affected.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// for r in exec_duration::fetch_results().iter() {
+///     println!("[{}] {} calls since last report", r.get_name(), r.get_exec_count());
+/// }
+/// exec_duration::reset_counts();
+/// ```
+pub fn reset_counts() {
+    manager::with_instance(|ctx| ctx.reset_counts())
+}
+
+/// Get the ratio of the instrumentation overhead to the total measured duration of the probes.
+/// The overhead is the time spent by the library registering and reporting the probes (metrics lock, aggregation,
+/// callbacks, ...) since the results were last drained; the cost of the points is not included.
+/// Nothing is drained by this function, even in auto-drain mode.
+/// A ratio approaching or exceeding 1 means the probes are too fine-grained for the work they measure.
+/// Returns 0 when nothing was measured.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// if exec_duration::overhead_ratio() > 0.1 {
+///     println!("instrumentation is too fine-grained");
+/// }
+/// ```
+pub fn overhead_ratio() -> f64 {
+    let measured = manager::with_instance(|ctx| ctx.total_matching(""));
+    overhead::ratio(measured)
+}
+
+/// Enable the rolling time window mode.
+/// Executions are additionally aggregated in a ring of `buckets` time buckets of `bucket_width` each,
+/// allowing `fetch_results_window` to report the recent activity only. Enabling the mode again with another
+/// configuration discards the recent activity recorded so far.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use std::time::Duration;
+///
+/// // keep the last 60 seconds, with 1 second resolution
+/// exec_duration::set_window(Duration::from_secs(1), 60);
+/// ```
+pub fn set_window(bucket_width: Duration, buckets: usize) {
+    let config = window::WindowConfig::new(bucket_width, buckets);
+    manager::with_instance(|ctx| ctx.set_window(config))
+}
+
+/// Enable the histogram mode, counting the executions of each probe per duration bucket.
+/// `bounds` are the upper bounds of the buckets, an extra bucket holds the executions beyond the last one.
+/// Calling this function again replaces the buckets and resets the counts, see `ExecDuration::get_mode_bucket`.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use std::time::Duration;
+///
+/// // cache hits below 1ms, misses between 1ms and 100ms
+/// exec_duration::set_histogram(&[Duration::from_millis(1), Duration::from_millis(100)]);
+/// ```
+pub fn set_histogram(bounds: &[Duration]) {
+    let bounds = bounds.iter().map(|b| b.as_nanos()).collect();
+    manager::with_instance(|ctx| ctx.set_histogram(bounds))
+}
+
+/// Retain a uniform sample of at most `capacity` execution durations per probe, see `ExecDuration::get_trimmed_mean`.
+/// Calling this function again replaces the capacity and discards the retained samples.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// exec_duration::set_sample_retention(1000);
+/// ```
+pub fn set_sample_retention(capacity: usize) {
+    manager::with_instance(|ctx| ctx.set_sample_retention(capacity))
+}
+
+/// Track an exponential moving average of the execution durations per probe, see `ExecDuration::get_ema`.
+/// Each execution moves the average by `alpha` (clamped to `0..=1`) of its difference with the execution duration:
+/// the higher `alpha`, the more weight on the recent executions. Calling this function again replaces `alpha`
+/// and keeps the current averages.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// exec_duration::set_ema_alpha(0.2);
+/// ```
+pub fn set_ema_alpha(alpha: f64) {
+    manager::with_instance(|ctx| ctx.set_ema_alpha(alpha))
+}
+
+/// Fetch execution metrics of the last `window` (rounded up to the bucket width, and at most the whole ring).
+/// The rolling time window mode must have been enabled using `set_window`, the results are empty otherwise.
+/// Only the execution counts and durations are aggregated per bucket, the results hold no elements.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ExecProbe;
+/// use std::time::Duration;
+///
+/// exec_duration::set_window(Duration::from_secs(1), 60);
+/// let mut ep = ExecProbe::new("main");
+///
+/// // code
+///
+/// // fetch the last 10 seconds
+/// let list = exec_duration::fetch_results_window(Duration::from_secs(10));
+/// for r in list.iter() {
+///     println!("{}", r);
+/// }
+/// ```
+pub fn fetch_results_window(window: Duration) -> Vec<output::ExecDuration> {
+    manager::with_instance(|ctx| ctx.fetch_results_window(window))
+}
+
+/// Fetch execution metrics grouped by name prefix.
+/// Probe names are split on the first `separator` and a synthetic parent is built per prefix,
+/// holding the matching probes as children and summing their counts and durations.
+/// A probe name without separator makes a group on its own.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ExecProbe;
+///
+/// let mut ep = ExecProbe::new("db.query");
+///
+/// // code
+///
+/// // fetch results
+/// let list = exec_duration::fetch_grouped('.');
+/// for group in list.iter() {
+///     println!("{}", group);
+/// }
+/// ```
+pub fn fetch_grouped(separator: char) -> Vec<output::ExecDuration> {
+    fetch_grouped_by(|r| {
+        r.get_name()
+            .split(separator)
+            .next()
+            .unwrap_or_default()
+            .to_string()
+    })
+}
+
+/// Fetch execution metrics grouped by name prefix, like `fetch_grouped`, splitting the probe names on the
+/// separator set with `output::set_name_separator`. Without a configured separator, every probe makes a group
+/// on its own.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::output;
+/// use exec_duration::ExecProbe;
+///
+/// output::set_name_separator("::");
+/// let mut ep = ExecProbe::new("db::query");
+///
+/// // code
+///
+/// // fetch results
+/// let list = exec_duration::fetch_grouped_by_name_separator();
+/// for group in list.iter() {
+///     println!("{}", group);
+/// }
+/// ```
+pub fn fetch_grouped_by_name_separator() -> Vec<output::ExecDuration> {
+    let separator = output::name_separator();
+    fetch_grouped_by(|r| match separator.as_deref() {
+        Some(separator) => r
+            .get_name()
+            .split(separator)
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+        None => r.get_name().to_string(),
+    })
+}
+
+/// Run `f` within the named session, the executions of the probes created by the current thread within the closure
+/// are additionally aggregated in a store specific to the session, retrieved using `fetch_session_results`.
+/// Sessions compose: the executions of a nested session are part of all the enclosing sessions too.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ExecProbe;
+///
+/// exec_duration::with_session("startup", || {
+///     let mut ep = ExecProbe::new("load_config");
+///     ep.add_point("read");
+/// });
+/// ```
+pub fn with_session<F, R>(name: &str, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    session::with_session(name, f)
+}
+
+/// Run `f` with a collector capturing the executions of the probes created by the current thread within the closure,
+/// instead of the global store. Returns the result of `f` and the captured metrics, handy to test a module in isolation.
+/// Executions reported after the closure returned, by probes created within it, are dropped.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ExecProbe;
+///
+/// let (value, results) = exec_duration::scoped_collector(|| {
+///     let mut ep = ExecProbe::new("parse");
+///     ep.add_point("tokenize");
+///     42
+/// });
+/// assert_eq!(value, 42);
+/// assert_eq!(results[0].get_name(), "parse");
+/// ```
+pub fn scoped_collector<F, T>(f: F) -> (T, Vec<output::ExecDuration>)
+where
+    F: FnOnce() -> T,
+{
+    collector::scoped_collector(f)
+}
+
+/// Fetch the execution metrics recorded within the given session, see `with_session`.
+/// The results are empty if nothing was recorded in the session.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ExecProbe;
+///
+/// exec_duration::with_session("startup", || {
+///     let mut ep = ExecProbe::new("load_config");
+///     ep.add_point("read");
+/// });
+///
+/// for r in exec_duration::fetch_session_results("startup").iter() {
+///     println!("{}", r);
+/// }
+/// ```
+pub fn fetch_session_results(name: &str) -> Vec<output::ExecDuration> {
+    manager::with_instance(|ctx| ctx.fetch_session_results(name))
+}
+
+/// Fetch execution metrics grouped by the given key function.
+/// The function is applied to each probe and a synthetic parent is built per resulting key,
+/// holding the matching probes as children and summing their counts and durations.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ExecProbe;
+///
+/// let mut ep = ExecProbe::builder("http_request").label("method", "GET").build();
+///
+/// // code
+///
+/// // fetch results grouped by method
+/// let list = exec_duration::fetch_grouped_by(|r| {
+///     r.get_labels().get("method").cloned().unwrap_or_default()
+/// });
+/// for group in list.iter() {
+///     println!("{}", group);
+/// }
+/// ```
+pub fn fetch_grouped_by<F>(key: F) -> Vec<output::ExecDuration>
+where
+    F: Fn(&output::ExecDuration) -> String,
+{
+    output::group_by(snapshot(), key)
+}
+
+/// Fetch the names of the probes that have recorded at least one execution.
+/// This is much cheaper than `fetch_results` when only the names are needed.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ExecProbe;
+///
+/// let mut ep = ExecProbe::new("main");
+///
+/// // code
+///
+/// for name in exec_duration::probe_names() {
+///     println!("{}", name);
+/// }
+/// ```
+pub fn probe_names() -> Vec<String> {
+    manager::with_instance(|ctx| ctx.probe_names())
+}
+
+/// Get the total duration of all the probes whose name starts with the given prefix.
+/// This is much cheaper than summing the results of `fetch_results`, labeled variants are all accounted.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// println!("total DB time: {:?}", exec_duration::total_matching("db."));
+/// ```
+pub fn total_matching(prefix: &str) -> Duration {
+    let total = manager::with_instance(|ctx| ctx.total_matching(prefix));
+    Duration::from_nanos(total as u64)
+}
+
+/// Check whether at least one execution has been recorded.
+/// This is much cheaper than checking whether `fetch_results` is empty.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// if exec_duration::has_data() {
+///     for r in exec_duration::fetch_results().iter() {
+///         println!("{}", r);
+///     }
+/// }
+/// ```
+pub fn has_data() -> bool {
+    manager::with_instance(|ctx| ctx.has_data())
+}
+
+/// Get an estimation of the memory used by the collected metrics, in bytes.
+/// The estimation accounts for the maps holding the metrics, their names and structures.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ExecProbe;
+///
+/// let mut ep = ExecProbe::new("main");
+///
+/// // code
+///
+/// println!("metrics use ~{} bytes", exec_duration::estimated_memory_bytes());
+/// ```
+pub fn estimated_memory_bytes() -> usize {
+    manager::with_instance(|ctx| ctx.estimated_memory_bytes())
+}
+
+/// Declare a performance budget for the given probe name.
+/// The budget is attached to the results of the probe, flagged by `ExecDuration::is_over_budget`
+/// when the average execution time exceeds it. Declaring a budget again replaces the previous one.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use std::time::Duration;
+///
+/// // one frame at 60 fps
+/// exec_duration::set_budget("render", Duration::from_millis(16));
+/// ```
+pub fn set_budget(name: &str, budget: Duration) {
+    manager::with_instance(|ctx| ctx.set_budget(name, budget.as_nanos()))
+}
+
+/// Enable or disable the auto-drain mode (disabled by default).
+/// In this mode, `fetch_results` resets the aggregates of the probes after fetching them, so that the collection
+/// only holds the activity since the last fetch. This keeps the memory flat for always-on reporters.
+/// The recent activity of the rolling time window is drained as well, sessions are not.
+/// Only `fetch_results` and `fetch_results_into` drain: the other helpers, such as `fetch_hotspots`
+/// or the exporters, leave the aggregates in place.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// exec_duration::set_auto_drain(true);
+///
+/// // periodic report of the activity since the previous one
+/// for r in exec_duration::fetch_results().iter() {
 ///     println!("{}", r);
 /// }
 /// ```
-pub fn fetch_results() -> Vec<output::ExecDuration> {
-    let ctx = manager::get_instance();
-    unsafe {
-        let ctx: &mut manager::ExecProbeManager = &mut *ctx;
-        ctx.fetch_results()
-    }
+pub fn set_auto_drain(enabled: bool) {
+    manager::with_instance(|ctx| ctx.set_auto_drain(enabled))
+}
+
+/// Set the behavior of the probes when the clock goes backward between two timestamps (`Drop` by default).
+/// The system clock may be adjusted while a probe runs, the anomalous delta is then either dropped,
+/// recorded as zero or clamped to the latest timestamp seen.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ClockAnomalyPolicy;
+///
+/// exec_duration::set_clock_anomaly_policy(ClockAnomalyPolicy::Saturate);
+/// ```
+pub fn set_clock_anomaly_policy(policy: ClockAnomalyPolicy) {
+    clock::set_anomaly_policy(policy)
+}
+
+/// Discard the executions longer than `max`, such as a probe stopped hours after being started by mistake,
+/// so that they do not dominate the aggregates. Discarded executions are only counted,
+/// see `ExecDuration::get_clamped_count`. Setting the clamp again replaces the previous one.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use std::time::Duration;
+///
+/// exec_duration::set_sample_clamp(Duration::from_secs(60));
+/// ```
+pub fn set_sample_clamp(max: Duration) {
+    manager::with_instance(|ctx| ctx.set_sample_clamp(max.as_nanos()))
+}
+
+/// Declare `alias` as another name of the probe `canonical`, the executions reported under `alias` are aggregated
+/// under `canonical`. Aliases resolve transitively, which allows renaming a probe several times.
+/// Fails with `ValidationError::AliasCycle` if the alias would make a cycle.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ExecProbe;
+///
+/// // `load` was renamed `load_config`
+/// exec_duration::add_alias("load", "load_config").unwrap();
+///
+/// let mut ep = ExecProbe::new("load");
+/// ep.add_point("read");
+/// ```
+pub fn add_alias(alias: &str, canonical: &str) -> Result<(), ValidationError> {
+    manager::with_instance(|ctx| ctx.add_alias(alias, canonical))
+}
+
+/// Declare a probe along with a description of what it measures, declaring it again replaces the description.
+/// Declarations are independent of the collected metrics: they document the instrumentation points of the program
+/// before any execution is reported, and are not cleared when the results are drained.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// exec_duration::declare("http_request", "handling of an HTTP request, from parsing to response");
+/// ```
+pub fn declare(name: &str, description: &str) {
+    manager::with_instance(|ctx| ctx.declare(name, description))
+}
+
+/// Get the probes declared with `declare`, as `(name, description)` pairs sorted by name
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// exec_duration::declare("http_request", "handling of an HTTP request");
+/// for (name, description) in exec_duration::registered_probes() {
+///     println!("{}: {}", name, description);
+/// }
+/// ```
+pub fn registered_probes() -> Vec<(String, String)> {
+    manager::with_instance(|ctx| ctx.registered_probes())
+}
+
+/// Register a probe aggregated with atomic counters and get a handle to report its executions.
+/// Reporting through the handle bypasses the metrics map entirely, registering the same name again returns a handle
+/// on the same counters. Fast probes are part of the results returned by `fetch_results`.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// let handle = exec_duration::register_fast("hot_function");
+/// for _ in 0..1000 {
+///     let _probe = handle.start();
+///     // code
+/// }
+/// ```
+pub fn register_fast(name: &str) -> FastProbeHandle {
+    manager::with_instance(|ctx| FastProbeHandle::new(ctx.register_fast(name)))
+}
+
+/// Aggregate the given events offline, through the same pipeline as the probes, into a fresh store.
+/// The current settings apply (aliases, aggregators, budgets, clamp, histogram buckets, sample retention), so that recorded events
+/// can be analyzed again with other settings. The collected metrics and the callbacks are left untouched.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::{Point, RawEvent};
+/// use std::time::Duration;
+///
+/// let events = vec![
+///     RawEvent::new("main", Duration::from_millis(3), vec![Point::new("part 1", Duration::from_millis(3))]),
+///     RawEvent::new("main", Duration::from_secs(90), vec![Point::new("part 1", Duration::from_secs(90))]),
+/// ];
+/// exec_duration::set_sample_clamp(Duration::from_secs(60));
+/// let list = exec_duration::replay(&events);
+/// assert_eq!(list[0].get_exec_count(), 1);
+/// assert_eq!(list[0].get_clamped_count(), 1);
+/// ```
+pub fn replay(events: &[RawEvent]) -> Vec<output::ExecDuration> {
+    manager::with_instance(|ctx| ctx.replay(events))
+}
+
+/// Install a storage backend replacing the in-memory store, see `Store`.
+/// Executions reported from now on reach the store, as well as the ones merged by `bulk_import`.
+/// The queries (`fetch_results`, `fetch_top`, `probe_names`, `has_data`, `total_matching`, ...) read its aggregates
+/// along with the ones of the fast probes, and the auto-drain mode resets it. Sessions are still recorded in memory,
+/// while the rolling time window, the histogram buckets and the sample retention are not applied to the store.
+/// Installing a store again replaces the previous one.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::output::ExecDuration;
+/// use exec_duration::{Point, Store};
+/// use std::collections::BTreeMap;
+/// use std::time::Duration;
+///
+/// #[derive(Default)]
+/// struct Counts(BTreeMap<String, u64>);
+///
+/// impl Store for Counts {
+///     fn report(&mut self, name: &str, _: &BTreeMap<String, String>, _: Duration, _: &[Point]) {
+///         *self.0.entry(name.to_string()).or_default() += 1;
+///     }
+///
+///     fn fetch(&self) -> Vec<ExecDuration> {
+///         self.0
+///             .iter()
+///             .filter_map(|(name, count)| ExecDuration::builder(name).count(*count).build().ok())
+///             .collect()
+///     }
+///
+///     fn reset(&mut self) {
+///         self.0.clear();
+///     }
+/// }
+///
+/// exec_duration::set_store(Counts::default());
+/// ```
+pub fn set_store<S: Store + 'static>(store: S) {
+    manager::with_instance(|ctx| ctx.set_store(Box::new(store)))
+}
+
+/// Register a custom aggregation closure for the given probe name.
+/// The closure is invoked each time an execution of the probe is reported, with the probe aggregation state
+/// and the execution duration in nanoseconds. The state is exposed through `ExecDuration::get_custom`.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::{CustomAgg, ExecProbe};
+///
+/// // keep the longest execution duration
+/// exec_duration::set_aggregator("main", |agg: &mut CustomAgg, duration| {
+///     let max = agg.get_or_default::<u128>();
+///     if duration > *max {
+///         *max = duration;
+///     }
+/// });
+///
+/// let mut ep = ExecProbe::new("main");
+/// ```
+pub fn set_aggregator<F>(name: &str, f: F)
+where
+    F: Fn(&mut CustomAgg, output::DurationUnit) + Send + Sync + 'static,
+{
+    manager::with_instance(|ctx| ctx.set_aggregator(name, std::sync::Arc::new(f)));
 }
 
 impl ExecProbe {
@@ -115,12 +1488,97 @@ impl ExecProbe {
     /// let ep = ExecProbe::new("main");
     /// ```
     pub fn new(name: &str) -> Self {
+        let mut data = manager::ExecData::new(name);
+        data.track_live();
+        ExecProbe {
+            data,
+            stop_done: false,
+        }
+    }
+
+    /// Create a new instance with room for at least `capacity` points,
+    /// avoiding reallocations when many points are added
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::with_point_capacity("main", 64);
+    /// for _ in 0..64 {
+    ///     ep.add_point("iteration");
+    /// }
+    /// ```
+    pub fn with_point_capacity(name: &str, capacity: usize) -> Self {
+        let mut data = manager::ExecData::new(name);
+        data.points.reserve_exact(capacity);
+        data.track_live();
         ExecProbe {
-            data: manager::ExecData::new(name),
+            data,
             stop_done: false,
         }
     }
 
+    /// Create a new instance correlated with the given distributed tracing context.
+    /// The ids are part of the serialized probe and are given to the callbacks registered with `on_report_traced`.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::with_trace_context("handler", 0x4bf92f3577b34da6a3ce929d0e0e4736, 0x00f067aa0ba902b7);
+    /// ep.add_point("line 1");
+    /// ```
+    pub fn with_trace_context(name: &str, trace_id: u128, span_id: u64) -> Self {
+        let mut ep = ExecProbe::new(name);
+        ep.data.trace = Some(TraceContext { trace_id, span_id });
+        ep
+    }
+
+    /// Get the distributed tracing context of this execution, if any
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let ep = ExecProbe::with_trace_context("handler", 1, 2);
+    /// assert_eq!(ep.trace_context().unwrap().span_id, 2);
+    /// ```
+    pub fn trace_context(&self) -> Option<TraceContext> {
+        self.data.trace
+    }
+
+    /// Get the correlation id of the current thread when this probe was created, if any.
+    /// It is part of the serialized probe.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let _id = exec_duration::set_correlation_id("req-42");
+    /// let ep = ExecProbe::new("handler");
+    /// assert_eq!(ep.correlation_id(), Some("req-42"));
+    /// ```
+    pub fn correlation_id(&self) -> Option<&str> {
+        self.data.correlation_id.as_deref()
+    }
+
+    /// Create a new builder to configure the probe before starting it
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let ep = ExecProbe::builder("main").record_points(false).build();
+    /// ```
+    pub fn builder(name: &str) -> ExecProbeBuilder {
+        ExecProbeBuilder::new(name)
+    }
+
     /// Add a new point
     ///
     /// # Examples
@@ -135,6 +1593,157 @@ impl ExecProbe {
         self.data.add_point(name);
     }
 
+    /// Add a new point with a duration measured externally, instead of the time elapsed since the previous point.
+    /// The next point is measured from the end of this one, that is the previous point moved forward by `duration`.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    /// use std::time::Duration;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    /// ep.add_point_duration("gpu", Duration::from_millis(42));
+    /// assert_eq!(ep.points()[0].get_duration(), Duration::from_millis(42));
+    /// ```
+    pub fn add_point_duration(&mut self, name: &str, duration: Duration) {
+        self.data.add_point_duration(name, duration);
+    }
+
+    /// Split the execution of a queued task in two phases: the time elapsed before this call is reported as a `wait`
+    /// element, the time from this call until the probe is stopped as a `service` element.
+    /// Only the first call has an effect.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// // created when the task is enqueued
+    /// let mut ep = ExecProbe::new("task");
+    ///
+    /// // dequeued by a worker
+    /// ep.mark_service_start();
+    /// // process the task
+    /// ep.stop();
+    /// ```
+    pub fn mark_service_start(&mut self) {
+        self.data.mark_service_start();
+    }
+
+    /// Start an independent sub-timer, its span until it is dropped is reported as an element of this probe.
+    /// Unlike points, several sub-timers may run at once, measuring concurrent operations with overlapping spans.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    /// {
+    ///     let _read = ep.timer("read");
+    ///     let _parse = ep.timer("parse");
+    ///     // code
+    /// }
+    /// ep.add_point("end");
+    /// ```
+    pub fn timer(&mut self, name: &str) -> SubTimer {
+        SubTimer::new(name, self.data.clock, self.data.timers.clone())
+    }
+
+    /// Get the points added so far to this execution
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    /// ep.add_point("line 1");
+    /// assert_eq!(ep.points()[0].get_name(), "line 1");
+    /// ```
+    pub fn points(&self) -> &[Point] {
+        &self.data.points
+    }
+
+    /// Iterate over the points added so far to this execution, as `(name, offset, duration)` tuples
+    /// where `offset` is the time elapsed from the probe start until the point was added,
+    /// that is the running sum of the point durations
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    /// ep.add_point("line 1");
+    /// ep.add_point("line 2");
+    /// for (name, offset, duration) in ep.point_offsets() {
+    ///     println!("[{}] from {:?} to {:?}", name, offset - duration, offset);
+    /// }
+    /// ```
+    pub fn point_offsets(&self) -> impl Iterator<Item = (&str, Duration, Duration)> + '_ {
+        self.data
+            .points
+            .iter()
+            .scan(Duration::default(), |offset, p| {
+                *offset += p.get_duration();
+                Some((p.get_name(), *offset, p.get_duration()))
+            })
+    }
+
+    /// Accumulate a numeric counter alongside the timing, such as a number of processed bytes.
+    /// Counters are summed over all executions and exposed by `ExecDuration::get_counters`.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    /// ep.add_counter("bytes", 512);
+    /// ep.add_counter("bytes", 512);
+    /// ep.add_point("line 1");
+    /// ```
+    pub fn add_counter(&mut self, name: &str, value: u64) {
+        self.data.add_counter(name, value);
+    }
+
+    /// Pause the timer, the time elapsed until `resume` is called is excluded from the total and from the next point
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    /// ep.add_point("line 1");
+    /// ep.pause();
+    /// // wait for user input
+    /// ep.resume();
+    /// ep.add_point("line 2");
+    /// ```
+    pub fn pause(&mut self) {
+        self.data.pause();
+    }
+
+    /// Resume the timer paused by `pause`
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    /// ep.pause();
+    /// // wait for user input
+    /// ep.resume();
+    /// ep.add_point("line 1");
+    /// ```
+    pub fn resume(&mut self) {
+        self.data.resume();
+    }
+
     /// Stop metrics and commit
     ///
     /// In most cases a call to this function is optional because ExecProbe implements the Drop trait and when an ExecProbe instance goes out of scope, a call to `stop` function will be performed
@@ -180,6 +1789,23 @@ pub struct ExecProbe {
     stop_done: bool,
 }
 
+/// Opaque measurement token.
+/// Instances are created using `exec_duration::start` function and consumed by `exec_duration::finish`.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// let token = exec_duration::start("callback");
+/// exec_duration::finish(token);
+/// ```
+#[derive(Debug)]
+#[must_use = "the measurement is only reported when the token is given to `exec_duration::finish`"]
+pub struct Token {
+    name: String,
+    begin: Instant,
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -207,7 +1833,7 @@ mod tests {
 
         let list = crate::fetch_results();
         assert_eq!(list.len(), 1);
-        let r = list.get(0).unwrap();
+        let r = list.first().unwrap();
         assert_eq!(r.get_name(), MAIN);
         assert_eq!(r.get_exec_count(), NB);
         assert_le!(
@@ -226,8 +1852,9 @@ mod tests {
             r.get_total_duration().as_millis(),
             ((SLEEP_1 + SLEEP_2) * NB) as u128
         );
-        assert_eq!(r.get_elements().len(), 2);
-        let v = r.get_elements().get(0).unwrap();
+        assert_eq!(r.get_elements().len(), 3);
+        assert_eq!(r.get_elements()[2].get_name(), crate::output::TAIL);
+        let v = r.get_elements().first().unwrap();
         assert_eq!(v.get_name(), FUNC_1);
         assert_eq!(v.get_exec_count(), NB);
         assert_le!(v.get_avg_duration().as_millis(), (SLEEP_1 + 1) as u128);
@@ -251,6 +1878,14 @@ mod tests {
         assert_eq!(v.get_elements().len(), 0);
     }
 
+    #[test]
+    fn point_capacity() {
+        let ep = ExecProbe::with_point_capacity(MAIN, 64);
+        assert_ge!(ep.data.points.capacity(), 64);
+        assert!(ep.data.points.is_empty());
+        assert_eq!(ep.data.name, MAIN);
+    }
+
     fn func1() {
         sleep(time::Duration::from_millis(SLEEP_1));
     }
@@ -0,0 +1,119 @@
+use crate::clock::Clock;
+use crate::manager::Labels;
+use crate::output::DurationUnit;
+use rustc_hash::FxHashMap as HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime};
+
+/// Registry of the probes alive, by id
+static LIVE: Mutex<Option<HashMap<u64, Live>>> = Mutex::new(None);
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+/// Whether the probes are registered as alive, see `exec_duration::set_live_tracking`
+static TRACKING: AtomicBool = AtomicBool::new(false);
+/// Whether `flush_live` was ever called, stopping a probe has to discard its provisional sample then
+static FLUSHED: AtomicBool = AtomicBool::new(false);
+
+fn lock() -> MutexGuard<'static, Option<HashMap<u64, Live>>> {
+    LIVE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// State of a probe alive, enough to compute its elapsed time so far
+#[derive(Clone, Debug)]
+pub(crate) struct Live {
+    pub name: String,
+    pub labels: Labels,
+    pub clock: Clock,
+    pub begin: SystemTime,
+    pub paused_at: Option<SystemTime>,
+    pub deadline: Option<Duration>,
+}
+
+impl Live {
+    fn elapsed(&self) -> Duration {
+        let now = self.paused_at.unwrap_or_else(|| self.clock.now());
+        now.duration_since(self.begin).unwrap_or_default()
+    }
+
+    fn overdue(&self) -> bool {
+        self.deadline.is_some_and(|d| self.elapsed() > d)
+    }
+}
+
+/// Elapsed time so far of a probe alive
+#[derive(Clone, Debug)]
+pub(crate) struct Provisional {
+    pub name: String,
+    pub labels: Labels,
+    pub duration: DurationUnit,
+}
+
+pub(crate) fn register(live: Live) -> u64 {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    lock().get_or_insert_with(HashMap::default).insert(id, live);
+    id
+}
+
+pub(crate) fn update(id: u64, begin: SystemTime, paused_at: Option<SystemTime>) {
+    if let Some(live) = lock().as_mut().and_then(|m| m.get_mut(&id)) {
+        live.begin = begin;
+        live.paused_at = paused_at;
+    }
+}
+
+pub(crate) fn unregister(id: u64) {
+    if let Some(m) = lock().as_mut() {
+        m.remove(&id);
+    }
+}
+
+pub(crate) fn tracking() -> bool {
+    TRACKING.load(Ordering::Relaxed)
+}
+
+pub(crate) fn set_tracking(enabled: bool) {
+    TRACKING.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn flushed() -> bool {
+    FLUSHED.load(Ordering::Relaxed)
+}
+
+/// Abandon the probes alive past their deadline, returns their ids
+pub(crate) fn abandon_overdue() -> Vec<u64> {
+    let mut guard = lock();
+    let live = match guard.as_mut() {
+        Some(live) => live,
+        None => return Vec::new(),
+    };
+    let overdue: Vec<u64> = live
+        .iter()
+        .filter(|(_, l)| l.overdue())
+        .map(|(id, _)| *id)
+        .collect();
+    for id in overdue.iter() {
+        live.remove(id);
+    }
+    overdue
+}
+
+/// Elapsed time so far of all the probes alive
+pub(crate) fn snapshot() -> HashMap<u64, Provisional> {
+    FLUSHED.store(true, Ordering::Relaxed);
+    abandon_overdue();
+    let guard = lock();
+    let live = match guard.as_ref() {
+        Some(live) => live,
+        None => return HashMap::default(),
+    };
+    live.iter()
+        .map(|(id, l)| {
+            let provisional = Provisional {
+                name: l.name.to_string(),
+                labels: l.labels.clone(),
+                duration: l.elapsed().as_nanos(),
+            };
+            (*id, provisional)
+        })
+        .collect()
+}
@@ -0,0 +1,60 @@
+use crate::Token;
+use std::ops::{Deref, DerefMut};
+use std::sync::{LockResult, Mutex, MutexGuard, PoisonError};
+
+/// Mutex guard measuring how long the lock is held.
+/// Instances are created using `exec_duration::timed_lock` function, the hold duration is reported as an execution
+/// of the probe when the guard goes out of scope.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use std::sync::Mutex;
+///
+/// let mutex = Mutex::new(0);
+/// {
+///     let mut value = exec_duration::timed_lock(&mutex, "counter_lock").unwrap();
+///     *value += 1;
+/// }
+/// ```
+#[derive(Debug)]
+pub struct LockTimer<'a, T: ?Sized> {
+    guard: MutexGuard<'a, T>,
+    token: Option<Token>,
+}
+
+impl<T: ?Sized> Deref for LockTimer<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T: ?Sized> DerefMut for LockTimer<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<T: ?Sized> Drop for LockTimer<'_, T> {
+    fn drop(&mut self) {
+        if let Some(token) = self.token.take() {
+            crate::finish(token);
+        }
+    }
+}
+
+pub(crate) fn timed_lock<'a, T: ?Sized>(
+    mutex: &'a Mutex<T>,
+    name: &str,
+) -> LockResult<LockTimer<'a, T>> {
+    let timer = |guard| LockTimer {
+        guard,
+        token: Some(crate::start(name)),
+    };
+    match mutex.lock() {
+        Ok(guard) => Ok(timer(guard)),
+        Err(e) => Err(PoisonError::new(timer(e.into_inner()))),
+    }
+}
@@ -0,0 +1,48 @@
+use crate::manager::ExecData;
+use log::Level;
+use std::time::Instant;
+
+/// Target of the log records emitted by `LoggedMeasure`
+const TARGET: &str = "exec_duration";
+
+/// Measurement guard recording a probe execution and logging its duration.
+/// Instances are created using `exec_duration::measure_logged` function, the wall time elapsed since the creation
+/// is reported and logged at the given level when the guard goes out of scope.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use log::Level;
+///
+/// {
+///     let _m = exec_duration::measure_logged("load_config", Level::Info);
+///     // code
+/// }
+/// ```
+#[derive(Debug)]
+#[must_use = "the measurement is reported as soon as the guard is dropped"]
+pub struct LoggedMeasure {
+    name: String,
+    level: Level,
+    begin: Instant,
+}
+
+impl LoggedMeasure {
+    pub(crate) fn new(name: &str, level: Level) -> Self {
+        LoggedMeasure {
+            name: name.to_string(),
+            level,
+            begin: Instant::now(),
+        }
+    }
+}
+
+impl Drop for LoggedMeasure {
+    fn drop(&mut self) {
+        let elapsed = self.begin.elapsed();
+        let mut data = ExecData::detached(&self.name);
+        data.record_points = false;
+        data.stop_with_duration(elapsed.as_nanos());
+        log::log!(target: TARGET, self.level, "[{}] took {:?}", self.name, elapsed);
+    }
+}
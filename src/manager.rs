@@ -1,112 +1,903 @@
+use crate::aggregator::{Aggregator, CustomAgg};
+use crate::call_tree::CallTree;
+use crate::clock;
+use crate::clock::{Clock, ClockAnomalyPolicy};
+use crate::collector;
+use crate::config::Config;
+use crate::correlation;
+use crate::depth;
+use crate::fast::FastCounter;
+use crate::gap;
+use crate::histogram::Histogram;
+use crate::live;
+use crate::live::{Live, Provisional};
 use crate::output;
 use crate::output::DurationUnit;
+use crate::overhead;
+use crate::replay::RawEvent;
+use crate::reservoir::Reservoir;
+use crate::session;
+use crate::store::Store;
+use crate::timer::Timers;
+use crate::trace::TraceContext;
+use crate::window::{Ring, WindowConfig};
+use crate::{InitError, ValidationError};
 use rustc_hash::FxHashMap as HashMap;
-use std::mem::transmute;
-use std::sync::Once;
-use std::time::SystemTime;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime};
 
-static START: Once = Once::new();
-static mut MANAGER: *mut ExecProbeManager = 0 as *mut ExecProbeManager;
+static MANAGER: Mutex<Option<ExecProbeManager>> = Mutex::new(None);
 
-pub(crate) fn get_instance() -> *mut ExecProbeManager {
-    START.call_once(|| unsafe {
-        let boxed = Box::new(ExecProbeManager::new());
-        MANAGER = transmute(boxed);
-    });
-    unsafe { MANAGER }
+fn lock() -> MutexGuard<'static, Option<ExecProbeManager>> {
+    // a panic in a user callback must not disable the metrics collection
+    MANAGER.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+pub(crate) fn init() -> Result<(), InitError> {
+    let mut guard = MANAGER.lock().map_err(|_| InitError::Poisoned)?;
+    if guard.is_none() {
+        *guard = Some(ExecProbeManager::new(Config::from_env()?));
+    }
+    Ok(())
+}
+
+pub(crate) fn shutdown() {
+    let manager = lock().take();
+    drop(manager);
+    overhead::reset();
+}
+
+/// Install `new` as the global collection, returns the previous one
+pub(crate) fn replace(new: ExecProbeManager) -> ExecProbeManager {
+    let old = lock().replace(new);
+    // the lock is released before building the default, which reads the environment
+    old.unwrap_or_else(|| ExecProbeManager::new(Config::from_env().unwrap_or_default()))
+}
+
+pub(crate) fn with_instance<R, F>(f: F) -> R
+where
+    F: FnOnce(&mut ExecProbeManager) -> R,
+{
+    let mut guard = lock();
+    // invalid environment variables are only reported by `init`
+    f(guard.get_or_insert_with(|| ExecProbeManager::new(Config::from_env().unwrap_or_default())))
 }
 
-#[derive(Clone, Eq, PartialEq, Debug)]
 pub(crate) struct ExecProbeManager {
     values: HashMap<String, Values>,
+    aggregators: HashMap<String, Aggregator>,
+    callbacks: Vec<ReportCallback>,
+    warmups: HashMap<String, u64>,
+    fast: Vec<Arc<FastCounter>>,
+    window: Option<WindowConfig>,
+    budgets: HashMap<String, DurationUnit>,
+    sessions: HashMap<String, HashMap<String, Values>>,
+    aliases: HashMap<String, String>,
+    declarations: BTreeMap<String, String>,
+    #[cfg(feature = "prometheus")]
+    descriptors: HashMap<String, crate::prometheus::Descriptor>,
+    clamp: Option<DurationUnit>,
+    config: Config,
+    sampling: f64,
+    provisional: HashMap<u64, Provisional>,
+    auto_drain: bool,
+    histogram: Option<Vec<DurationUnit>>,
+    retention: Option<usize>,
+    ema_alpha: Option<f64>,
+    collectors: HashMap<u64, HashMap<String, Values>>,
+    store: Option<Box<dyn Store>>,
+    calls: CallTree,
+}
+
+pub(crate) type ReportCallback =
+    Arc<dyn Fn(&str, Duration, &[Point], Option<&TraceContext>) + Send + Sync>;
+
+/// Execution handed to the report callbacks, invoked once the collection is unlocked
+pub(crate) struct Reported {
+    callbacks: Vec<ReportCallback>,
+    name: String,
+    duration: Duration,
+    points: Vec<Point>,
+    trace: Option<TraceContext>,
+}
+
+impl Reported {
+    fn notify(self) {
+        for cb in self.callbacks.iter() {
+            cb(&self.name, self.duration, &self.points, self.trace.as_ref());
+        }
+    }
+}
+
+pub(crate) type Labels = BTreeMap<String, String>;
+
+/// Names of the points splitting an execution at `ExecProbe::mark_service_start`
+pub(crate) const WAIT_POINT: &str = "wait";
+pub(crate) const SERVICE_POINT: &str = "service";
+
+/// Key of the aggregates of a probe, the probe name alone when it has no label
+fn series_key(name: &str, labels: &Labels) -> String {
+    let labels: Vec<String> = labels.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+    format!("{}{{{}}}", name, labels.join(","))
+}
+
+fn store_memory_bytes(store: &HashMap<String, Values>) -> usize {
+    let mut size = store.capacity() * std::mem::size_of::<(String, Values)>();
+    for (key, e) in store {
+        size += key.capacity();
+        size += e.values.capacity() * std::mem::size_of::<(String, Value)>();
+        size += e.values.keys().map(|k| k.capacity()).sum::<usize>();
+    }
+    size
+}
+
+/// Settings of the manager applied when aggregating an execution
+struct RecordSettings<'a> {
+    window: Option<&'a WindowConfig>,
+    aggregator: Option<&'a Aggregator>,
+    histogram: Option<&'a [DurationUnit]>,
+    retention: Option<usize>,
+    ema_alpha: Option<f64>,
+}
+
+/// Reason an execution is counted without contributing to the metrics
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Discarded {
+    /// Longer than the clamp, see `set_sample_clamp`
+    Clamped,
+    /// Not slower than the threshold of the probe, see `ExecProbeBuilder::record_if_slower_than`
+    Fast,
+}
+
+/// Aggregate an execution into the given store
+fn record(
+    store: &mut HashMap<String, Values>,
+    key: &str,
+    v: &ExecData,
+    settings: &RecordSettings,
+    discarded: Option<Discarded>,
+) {
+    if !store.contains_key(key) {
+        let values = Values {
+            name: v.name.to_string(),
+            labels: v.labels.clone(),
+            ..Default::default()
+        };
+        store.insert(key.to_string(), values);
+    }
+    let values = store.get_mut(key).unwrap();
+    match discarded {
+        Some(Discarded::Clamped) => values.clamped_count += 1,
+        Some(Discarded::Fast) => values.fast_count += 1,
+        None => {}
+    }
+    if discarded.is_some() {
+        return;
+    }
+    values.duration += v.duration;
+    values.sum_squares = values
+        .sum_squares
+        .saturating_add(v.duration.saturating_mul(v.duration));
+    values.count += 1;
+    if v.duration == 0 {
+        values.zero_count += 1;
+    }
+    values.min_duration = Some(
+        values
+            .min_duration
+            .map_or(v.duration, |m| m.min(v.duration)),
+    );
+    values.max_duration = Some(
+        values
+            .max_duration
+            .map_or(v.duration, |m| m.max(v.duration)),
+    );
+    values.max_depth = values.max_depth.max(v.max_depth);
+    #[cfg(feature = "tdigest")]
+    values.digest.record(v.duration);
+    if let Some(config) = settings.window {
+        values.ring.record(config, v.duration);
+    }
+    if let Some(bounds) = settings.histogram {
+        values
+            .histogram
+            .get_or_insert_with(|| Histogram::new(bounds))
+            .record(v.duration);
+    }
+    if let Some(alpha) = settings.ema_alpha {
+        let d = v.duration as f64;
+        values.ema = Some(values.ema.map_or(d, |e| e + alpha * (d - e)));
+    }
+    if let Some(capacity) = settings.retention {
+        values
+            .samples
+            .get_or_insert_with(|| Reservoir::new(capacity))
+            .record(v.duration);
+    }
+    for (name, value) in v.counters.iter() {
+        let counter = values.counters.entry(name.to_string()).or_insert(0);
+        *counter = counter.saturating_add(*value);
+    }
+    if let Some(f) = settings.aggregator {
+        f(&mut values.custom, v.duration);
+    }
+    for e in v.points.iter() {
+        if !values.values.contains_key(&e.name) {
+            values.values.insert(
+                e.name.to_string(),
+                Value {
+                    order: values.values.len(),
+                    count: e.hits,
+                    zero_count: if e.duration == 0 { e.hits } else { 0 },
+                    duration: e.duration,
+                    sum_squares: e.sum_squares(),
+                },
+            );
+        } else {
+            let value = values.values.get_mut(&e.name).unwrap();
+            value.duration += e.duration;
+            value.sum_squares = value.sum_squares.saturating_add(e.sum_squares());
+            value.count += e.hits;
+            if e.duration == 0 {
+                value.zero_count += e.hits;
+            }
+        }
+    }
 }
 
-#[derive(Clone, Eq, PartialEq, Debug, Default)]
+#[derive(Clone, Debug, Default)]
 struct Values {
+    name: String,
+    labels: Labels,
     duration: DurationUnit,
     count: u64,
+    zero_count: u64,
+    clamped_count: u64,
+    fast_count: u64,
+    sum_squares: DurationUnit,
+    min_duration: Option<DurationUnit>,
+    max_duration: Option<DurationUnit>,
+    max_depth: usize,
+    ema: Option<f64>,
     values: HashMap<String, Value>,
+    custom: CustomAgg,
+    ring: Ring,
+    histogram: Option<Histogram>,
+    samples: Option<Reservoir>,
+    counters: HashMap<String, u64>,
+    #[cfg(feature = "tdigest")]
+    digest: crate::digest::Digest,
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
 struct Value {
     order: usize,
     count: u64,
+    zero_count: u64,
     duration: DurationUnit,
+    sum_squares: DurationUnit,
 }
 
 impl ExecProbeManager {
-    pub fn new() -> Self {
+    pub fn new(config: Config) -> Self {
         Self {
             values: HashMap::default(),
+            aggregators: HashMap::default(),
+            callbacks: Vec::new(),
+            warmups: HashMap::default(),
+            fast: Vec::new(),
+            window: None,
+            budgets: HashMap::default(),
+            sessions: HashMap::default(),
+            aliases: HashMap::default(),
+            declarations: BTreeMap::new(),
+            #[cfg(feature = "prometheus")]
+            descriptors: HashMap::default(),
+            clamp: None,
+            config,
+            sampling: 0.0,
+            provisional: HashMap::default(),
+            auto_drain: false,
+            histogram: None,
+            retention: None,
+            ema_alpha: None,
+            collectors: HashMap::default(),
+            store: None,
+            calls: CallTree::default(),
+        }
+    }
+
+    pub fn on_report(&mut self, cb: ReportCallback) {
+        self.callbacks.push(cb);
+    }
+
+    pub fn set_aggregator(&mut self, name: &str, f: Aggregator) {
+        self.aggregators.insert(name.to_string(), f);
+    }
+
+    pub fn set_window(&mut self, config: WindowConfig) {
+        self.window = Some(config);
+        for e in self.values.values_mut() {
+            e.ring.clear();
+        }
+    }
+
+    pub fn set_histogram(&mut self, mut bounds: Vec<DurationUnit>) {
+        bounds.sort_unstable();
+        bounds.dedup();
+        let stores = std::iter::once(&mut self.values).chain(self.sessions.values_mut());
+        for e in stores.flat_map(|store| store.values_mut()) {
+            e.histogram = None;
+        }
+        self.histogram = Some(bounds);
+    }
+
+    pub fn set_sample_retention(&mut self, capacity: usize) {
+        let stores = std::iter::once(&mut self.values).chain(self.sessions.values_mut());
+        for e in stores.flat_map(|store| store.values_mut()) {
+            e.samples = None;
+        }
+        self.retention = Some(capacity);
+    }
+
+    pub fn set_ema_alpha(&mut self, alpha: f64) {
+        self.ema_alpha = Some(alpha.clamp(0.0, 1.0));
+    }
+
+    pub fn reset_counts(&mut self) {
+        let stores = std::iter::once(&mut self.values).chain(self.sessions.values_mut());
+        for e in stores.flat_map(|store| store.values_mut()) {
+            e.count = 0;
+            e.zero_count = 0;
+            e.clamped_count = 0;
+            e.fast_count = 0;
+            for value in e.values.values_mut() {
+                value.count = 0;
+                value.zero_count = 0;
+            }
+        }
+    }
+
+    pub fn record_call(&mut self, path: &[String], duration: DurationUnit) {
+        if self.config.enabled {
+            self.calls.record(path, duration);
+        }
+    }
+
+    pub fn set_store(&mut self, store: Box<dyn Store>) {
+        self.store = Some(store);
+    }
+
+    pub fn set_budget(&mut self, name: &str, budget: DurationUnit) {
+        self.budgets.insert(name.to_string(), budget);
+    }
+
+    fn apply_budgets(&self, res: &mut [output::ExecDuration]) {
+        if self.budgets.is_empty() {
+            return;
+        }
+        for r in res.iter_mut() {
+            r.set_budget(self.budgets.get(r.get_name()).copied());
+        }
+    }
+
+    pub fn set_auto_drain(&mut self, enabled: bool) {
+        self.auto_drain = enabled;
+    }
+
+    pub fn auto_drain(&self) -> bool {
+        self.auto_drain
+    }
+
+    pub fn set_sample_clamp(&mut self, max: DurationUnit) {
+        self.clamp = Some(max);
+    }
+
+    pub fn add_alias(&mut self, alias: &str, canonical: &str) -> Result<(), ValidationError> {
+        // the chain from `canonical` must not come back to `alias`, whatever `alias` points to so far
+        let mut name = canonical;
+        for _ in 0..=self.aliases.len() {
+            if name == alias {
+                return Err(ValidationError::AliasCycle(alias.to_string()));
+            }
+            match self.aliases.get(name) {
+                Some(next) => name = next,
+                None => break,
+            }
+        }
+        self.aliases
+            .insert(alias.to_string(), canonical.to_string());
+        Ok(())
+    }
+
+    pub fn declare(&mut self, name: &str, description: &str) {
+        self.declarations
+            .insert(name.to_string(), description.to_string());
+    }
+
+    pub fn registered_probes(&self) -> Vec<(String, String)> {
+        self.declarations
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[cfg(feature = "prometheus")]
+    pub fn describe(&mut self, name: &str, descriptor: crate::prometheus::Descriptor) {
+        self.descriptors.insert(name.to_string(), descriptor);
+    }
+
+    #[cfg(feature = "prometheus")]
+    pub fn descriptors(&self) -> &HashMap<String, crate::prometheus::Descriptor> {
+        &self.descriptors
+    }
+
+    /// Canonical name of a probe, following aliases transitively
+    fn resolve<'a>(&'a self, mut name: &'a str) -> &'a str {
+        // `add_alias` rejects cycles, the hop limit guards the metrics lock against an infinite loop anyway
+        for _ in 0..self.aliases.len() {
+            match self.aliases.get(name) {
+                Some(canonical) => name = canonical,
+                None => break,
+            }
+        }
+        name
+    }
+
+    pub fn register_fast(&mut self, name: &str) -> Arc<FastCounter> {
+        if let Some(c) = self.fast.iter().find(|c| c.name() == name) {
+            return c.clone();
+        }
+        let c = Arc::new(FastCounter::new(name));
+        self.fast.push(c.clone());
+        c
+    }
+
+    fn global_report(v: &mut ExecData) {
+        let overdue = v.deadline.is_some_and(|d| v.duration > d.as_nanos());
+        let reported = (!v.points.is_empty() || !v.record_points) && !overdue;
+        let provisional = v.live_id.filter(|_| live::flushed());
+        if reported || provisional.is_some() {
+            let reported = with_instance(|ctx| {
+                if let Some(id) = provisional {
+                    ctx.provisional.remove(&id);
+                }
+                if reported {
+                    ctx.report(v)
+                } else {
+                    None
+                }
+            });
+            // the callbacks may create probes or fetch the results, the collection must not be locked
+            if let Some(reported) = reported {
+                reported.notify();
+            }
         }
     }
 
-    fn unsafe_report(v: &mut ExecData) {
-        let ctx = get_instance();
-        if v.duration > 0 && !v.points.is_empty() {
-            unsafe {
-                let ctx: &mut ExecProbeManager = &mut *ctx;
-                ctx.report(v);
+    pub fn flush_live(&mut self) {
+        self.provisional = live::snapshot();
+    }
+
+    pub fn abandon_overdue(&mut self) {
+        for id in live::abandon_overdue() {
+            self.provisional.remove(&id);
+        }
+    }
+
+    /// Whether the execution is part of the sample, every `1 / sample_rate` execution is
+    fn sample(&mut self) -> bool {
+        if self.config.sample_rate >= 1.0 {
+            return true;
+        }
+        self.sampling += self.config.sample_rate;
+        if self.sampling < 1.0 {
+            return false;
+        }
+        self.sampling -= 1.0;
+        true
+    }
+
+    /// Aggregate the execution, returns it along with the callbacks to invoke when there are some
+    fn report(&mut self, v: &mut ExecData) -> Option<Reported> {
+        if !self.config.enabled || !self.sample() {
+            return None;
+        }
+        if !self.aliases.is_empty() {
+            let canonical = self.resolve(&v.name);
+            if canonical != v.name {
+                v.name = canonical.to_string();
+            }
+        }
+        if v.warmup > 0 {
+            let done = self.warmups.entry(v.name.to_string()).or_insert(0);
+            if *done < v.warmup {
+                *done += 1;
+                return None;
             }
         }
+        let reported = (!self.callbacks.is_empty()).then(|| Reported {
+            callbacks: self.callbacks.clone(),
+            name: v.name.to_string(),
+            duration: Duration::from_nanos(v.duration as u64),
+            points: v.points.clone(),
+            trace: v.trace,
+        });
+        self.aggregate(v);
+        reported
     }
 
-    fn report(&mut self, v: &mut ExecData) {
-        if !self.values.contains_key(&v.name) {
-            let values = Values {
-                values: HashMap::default(),
-                duration: 0,
-                count: 0,
-            };
-            self.values.insert(v.name.to_string(), values);
+    fn aggregate(&mut self, v: &mut ExecData) {
+        let labeled;
+        let key = if v.labels.is_empty() {
+            &v.name
+        } else {
+            labeled = series_key(&v.name, &v.labels);
+            &labeled
+        };
+        let settings = RecordSettings {
+            window: self.window.as_ref(),
+            aggregator: self.aggregators.get(&v.name),
+            histogram: self.histogram.as_deref(),
+            retention: self.retention,
+            ema_alpha: self.ema_alpha,
+        };
+        let discarded = if self.clamp.is_some_and(|max| v.duration > max) {
+            Some(Discarded::Clamped)
+        } else if v.slower_than.is_some_and(|t| v.duration <= t.as_nanos()) {
+            Some(Discarded::Fast)
+        } else {
+            None
+        };
+        if let Some(id) = v.collector {
+            // executions within a scoped collector stay out of the global store,
+            // they are dropped once the collector is closed
+            if let Some(store) = self.collectors.get_mut(&id) {
+                record(store, key, v, &settings, discarded);
+            }
+            v.points.clear();
+            return;
         }
-        let mut values = self.values.get_mut(&v.name).unwrap();
-        values.duration += v.duration;
-        values.count += 1;
-        while !v.points.is_empty() {
-            let e = v.points.remove(0);
-            if !values.values.contains_key(&e.name) {
-                values.values.insert(
-                    e.name.to_string(),
-                    Value {
-                        order: values.values.len(),
-                        count: 1,
-                        duration: e.duration,
-                    },
-                );
-            } else {
-                let mut value = values.values.get_mut(&e.name).unwrap();
-                value.duration += e.duration;
-                value.count += 1;
+        match self.store.as_mut() {
+            Some(store) => {
+                if discarded.is_none() {
+                    let duration = Duration::from_nanos(v.duration as u64);
+                    store.report(&v.name, &v.labels, duration, &v.points);
+                }
+            }
+            None => {
+                // the cap bounds the global aggregates only, sessions are bounded by their scope
+                let capped = self
+                    .config
+                    .max_probes
+                    .is_some_and(|max| self.values.len() >= max && !self.values.contains_key(key));
+                if !capped {
+                    record(&mut self.values, key, v, &settings, discarded);
+                }
             }
         }
+        for session in v.sessions.iter() {
+            let values = self.sessions.entry(session.to_string()).or_default();
+            record(values, key, v, &settings, discarded);
+        }
+        v.points.clear();
+    }
+
+    pub fn estimated_memory_bytes(&self) -> usize {
+        let mut size = std::mem::size_of::<Self>();
+        for (name, store) in &self.sessions {
+            size += name.capacity() + std::mem::size_of::<(String, HashMap<String, Values>)>();
+            size += store_memory_bytes(store);
+        }
+        size + store_memory_bytes(&self.values)
+    }
+
+    /// Get the aggregates of the installed store, empty without one
+    fn stored(&self) -> Vec<output::ExecDuration> {
+        self.store.as_ref().map(|s| s.fetch()).unwrap_or_default()
+    }
+
+    pub fn has_data(&self) -> bool {
+        self.values.values().any(|e| e.count > 0)
+            || self.fast.iter().any(|c| c.count() > 0)
+            || !self.calls.is_empty()
+            || self.stored().iter().any(|r| r.get_exec_count() > 0)
+    }
+
+    pub fn probe_names(&self) -> Vec<String> {
+        let fast = self.fast.iter().filter(|c| c.count() > 0);
+        let stored = self.stored();
+        let mut names: Vec<String> = self
+            .values
+            .values()
+            .map(|e| e.name.to_string())
+            .chain(fast.map(|c| c.name().to_string()))
+            .chain(stored.iter().map(|r| r.get_name().to_string()))
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    pub fn total_matching(&self, prefix: &str) -> DurationUnit {
+        let fast = self.fast.iter().filter(|c| c.name().starts_with(prefix));
+        let stored = self.stored();
+        let stored = stored.iter().filter(|r| r.get_name().starts_with(prefix));
+        self.values
+            .values()
+            .filter(|e| e.name.starts_with(prefix))
+            .map(|e| e.duration)
+            .chain(fast.map(|c| c.duration()))
+            .chain(stored.map(|r| r.get_total_duration().as_nanos()))
+            .sum()
     }
 
     pub fn fetch_results(&self) -> Vec<output::ExecDuration> {
-        let mut res: Vec<output::ExecDuration> = Vec::new();
-        for (key, e) in &self.values {
-            let mut elt = output::ExecDuration::new(&key, e.count, e.duration, e.duration);
-            let mut keys: Vec<String> = Vec::new();
-            for _ in e.values.keys() {
-                keys.push(String::new());
+        let mut res = Vec::new();
+        self.fetch_results_into(&mut res);
+        res
+    }
+
+    /// Fetch the results and reset the aggregates, for the auto-drain mode
+    pub fn drain_results(&mut self) -> Vec<output::ExecDuration> {
+        let mut res = Vec::new();
+        self.drain_results_into(&mut res);
+        res
+    }
+
+    pub fn fetch_results_into(&self, res: &mut Vec<output::ExecDuration>) {
+        let fast = self.fast.iter().filter_map(|c| c.to_exec_duration());
+        self.results_into(fast, res);
+    }
+
+    pub fn drain_results_into(&mut self, res: &mut Vec<output::ExecDuration>) {
+        let fast = self.fast.iter().filter_map(|c| c.drain());
+        self.results_into(fast, res);
+        self.values.clear();
+        self.provisional.clear();
+        self.calls.clear();
+        overhead::reset();
+        if let Some(store) = self.store.as_mut() {
+            store.reset();
+        }
+    }
+
+    /// Refill the buffer with the results, reusing its capacity
+    fn results_into(
+        &self,
+        fast: impl Iterator<Item = output::ExecDuration>,
+        res: &mut Vec<output::ExecDuration>,
+    ) {
+        res.clear();
+        for e in self.values.values() {
+            res.push(e.to_exec_duration());
+        }
+        if let Some(store) = self.store.as_ref() {
+            res.extend(store.fetch());
+        }
+        res.extend(fast);
+        for call in self.calls.results() {
+            match res
+                .iter_mut()
+                .find(|r| r.get_name() == call.get_name() && r.get_labels().is_empty())
+            {
+                Some(r) => r.merge(call),
+                None => res.push(call),
+            }
+        }
+        for p in self.provisional.values() {
+            let mut sample = output::ExecDuration::new(&p.name, 1, p.duration, p.duration);
+            sample.set_labels(p.labels.clone());
+            sample.set_provisional_count(1);
+            sample.set_sum_squares(Some(p.duration.saturating_mul(p.duration)));
+            sample.set_min_max(Some(p.duration), Some(p.duration));
+            match res
+                .iter_mut()
+                .find(|r| r.get_name() == p.name && *r.get_labels() == p.labels)
+            {
+                Some(r) => r.merge(sample),
+                None => res.push(sample),
+            }
+        }
+        self.apply_budgets(res);
+    }
+
+    /// Aggregate the given events with the settings of this manager (aliases, aggregators, clamp, ...),
+    /// into a fresh store
+    pub fn replay(&self, events: &[RawEvent]) -> Vec<output::ExecDuration> {
+        let mut ctx = ExecProbeManager::new(Config {
+            enabled: true,
+            sample_rate: 1.0,
+            max_probes: self.config.max_probes,
+        });
+        ctx.aggregators = self.aggregators.clone();
+        ctx.budgets = self.budgets.clone();
+        ctx.aliases = self.aliases.clone();
+        ctx.clamp = self.clamp;
+        ctx.histogram = self.histogram.clone();
+        ctx.retention = self.retention;
+        ctx.ema_alpha = self.ema_alpha;
+        for e in events.iter() {
+            let mut data = ExecData::detached(&e.name);
+            data.sessions.clear();
+            data.collector = None;
+            data.labels = e.labels.clone();
+            data.duration = e.duration;
+            data.points = e.points.clone();
+            ctx.report(&mut data);
+        }
+        ctx.fetch_results()
+    }
+
+    /// Merge executions aggregated elsewhere, each of them assumed to last the average of its entry
+    pub fn bulk_import(&mut self, entries: &[(String, u64, Duration)]) {
+        if !self.config.enabled {
+            return;
+        }
+        for (name, count, duration) in entries.iter().filter(|e| e.1 > 0) {
+            let name = self.resolve(name).to_string();
+            if let Some(store) = self.store.as_mut() {
+                store.import(&name, *count, *duration);
+                continue;
             }
-            for (name, v) in &e.values {
-                keys[v.order].push_str(name.as_str());
+            if let Some(max) = self.config.max_probes {
+                if self.values.len() >= max && !self.values.contains_key(&name) {
+                    continue;
+                }
             }
-            for name in keys.iter() {
-                let v = e.values.get(name).unwrap();
-                elt.add(output::ExecDuration::new(
-                    &name, v.count, v.duration, e.duration,
-                ));
+            let values = self.values.entry(name.to_string()).or_default();
+            values.name = name;
+            let duration = duration.as_nanos();
+            let avg = duration / *count as DurationUnit;
+            values.duration += duration;
+            values.count += count;
+            values.sum_squares = values
+                .sum_squares
+                .saturating_add((*count as DurationUnit).saturating_mul(avg.saturating_mul(avg)));
+            values.min_duration = Some(values.min_duration.map_or(avg, |m| m.min(avg)));
+            values.max_duration = Some(values.max_duration.map_or(avg, |m| m.max(avg)));
+        }
+    }
+
+    pub fn open_collector(&mut self, id: u64) {
+        self.collectors.insert(id, HashMap::default());
+    }
+
+    /// Remove the collector, returns the metrics it collected
+    pub fn close_collector(&mut self, id: u64) -> Vec<output::ExecDuration> {
+        let mut res: Vec<output::ExecDuration> = match self.collectors.remove(&id) {
+            Some(values) => values.values().map(|e| e.to_exec_duration()).collect(),
+            None => Vec::new(),
+        };
+        self.apply_budgets(&mut res);
+
+        res
+    }
+
+    pub fn fetch_session_results(&self, name: &str) -> Vec<output::ExecDuration> {
+        let mut res: Vec<output::ExecDuration> = match self.sessions.get(name) {
+            Some(values) => values.values().map(|e| e.to_exec_duration()).collect(),
+            None => Vec::new(),
+        };
+        self.apply_budgets(&mut res);
+
+        res
+    }
+
+    pub fn fetch_results_window(&self, window: Duration) -> Vec<output::ExecDuration> {
+        let config = match self.window.as_ref() {
+            Some(config) => config,
+            None => return Vec::new(),
+        };
+        let mut res: Vec<output::ExecDuration> = Vec::new();
+        for e in self.values.values() {
+            let (count, duration) = e.ring.sum(config, window);
+            if count > 0 {
+                let mut elt = output::ExecDuration::new(&e.name, count, duration, duration);
+                elt.set_labels(e.labels.clone());
+                res.push(elt);
+            }
+        }
+        self.apply_budgets(&mut res);
+
+        res
+    }
+
+    pub fn fetch_top(&self, n: usize, by: output::SortKey) -> Vec<output::ExecDuration> {
+        let fast = self.fast.iter().filter(|c| c.count() > 0);
+        let stored = self.stored();
+        let candidates = self
+            .values
+            .iter()
+            .map(|(name, e)| (by.key(e.count, e.duration), name.as_str()))
+            .chain(fast.map(|c| (by.key(c.count(), c.duration()), c.name())))
+            .chain(stored.iter().map(|r| {
+                let duration = r.get_total_duration().as_nanos();
+                (by.key(r.get_exec_count(), duration), r.get_name())
+            }));
+        let mut heap = BinaryHeap::with_capacity(n + 1);
+        for candidate in candidates {
+            heap.push(Reverse(candidate));
+            if heap.len() > n {
+                heap.pop();
             }
-            res.push(elt);
         }
 
+        let mut res: Vec<output::ExecDuration> = heap
+            .into_sorted_vec()
+            .into_iter()
+            .filter_map(|Reverse((_, name))| match self.values.get(name) {
+                Some(e) => Some(e.to_exec_duration()),
+                None => self
+                    .fast
+                    .iter()
+                    .find(|c| c.name() == name)
+                    .and_then(|c| c.to_exec_duration())
+                    .or_else(|| stored.iter().find(|r| r.get_name() == name).cloned()),
+            })
+            .collect();
+        self.apply_budgets(&mut res);
+
         res
     }
 }
 
+impl Values {
+    fn to_exec_duration(&self) -> output::ExecDuration {
+        let mut elt =
+            output::ExecDuration::new(&self.name, self.count, self.duration, self.duration);
+        elt.set_labels(self.labels.clone());
+        elt.set_zero_count(self.zero_count);
+        elt.set_clamped_count(self.clamped_count);
+        elt.set_fast_count(self.fast_count);
+        elt.set_histogram(self.histogram.clone());
+        if let Some(samples) = self.samples.as_ref() {
+            elt.set_samples(samples.samples().to_vec());
+        }
+        elt.set_sum_squares(Some(self.sum_squares));
+        elt.set_min_max(self.min_duration, self.max_duration);
+        elt.set_max_depth(self.max_depth);
+        elt.set_ema(self.ema.map(|e| e as DurationUnit));
+        #[cfg(feature = "tdigest")]
+        elt.set_percentiles(Some(self.digest.snapshot()));
+        elt.set_custom(self.custom.snapshot());
+        elt.set_counters(
+            self.counters
+                .iter()
+                .map(|(k, v)| (k.to_string(), *v))
+                .collect(),
+        );
+        let mut keys: Vec<String> = Vec::new();
+        for _ in self.values.keys() {
+            keys.push(String::new());
+        }
+        for (name, v) in &self.values {
+            keys[v.order].push_str(name.as_str());
+        }
+        let mut points_duration: DurationUnit = 0;
+        for name in keys.iter() {
+            let v = self.values.get(name).unwrap();
+            let mut child = output::ExecDuration::new(name, v.count, v.duration, self.duration);
+            child.set_zero_count(v.zero_count);
+            child.set_sum_squares(Some(v.sum_squares));
+            points_duration += v.duration;
+            elt.add(child);
+        }
+        let tail = self.duration.saturating_sub(points_duration);
+        if !keys.is_empty() && tail > 0 {
+            elt.add(output::ExecDuration::new(
+                output::TAIL,
+                self.count,
+                tail,
+                self.duration,
+            ));
+        }
+        elt
+    }
+}
+
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub(crate) struct ExecData {
@@ -115,42 +906,451 @@ pub(crate) struct ExecData {
     pub now: std::time::SystemTime,
     pub duration: DurationUnit,
     pub points: Vec<Point>,
+    pub record_points: bool,
+    pub merge_points: bool,
+    pub warmup: u64,
+    pub paused_at: Option<SystemTime>,
+    pub clock: Clock,
+    pub counters: BTreeMap<String, u64>,
+    pub labels: Labels,
+    pub sessions: Vec<String>,
+    pub collector: Option<u64>,
+    pub depth_base: Option<usize>,
+    pub max_depth: usize,
+    pub live_id: Option<u64>,
+    pub deadline: Option<Duration>,
+    pub slower_than: Option<Duration>,
+    pub trace: Option<TraceContext>,
+    pub correlation_id: Option<String>,
+    pub service_started: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub timers: Timers,
 }
 
 impl ExecData {
     pub fn new(name: &str) -> Self {
-        let now = std::time::SystemTime::now();
+        Self::with_clock(name, Clock::default())
+    }
+
+    pub fn with_clock(name: &str, clock: Clock) -> Self {
+        gap::started();
+        let mut data = Self::unbound(name, clock);
+        data.depth_base = Some(depth::begin());
+        data.correlation_id = correlation::current();
+        data
+    }
+
+    fn unbound(name: &str, clock: Clock) -> Self {
+        let now = clock.now();
         ExecData {
             name: name.to_string(),
             points: Vec::new(),
             begin_timestamp: now,
             now,
             duration: 0,
+            record_points: true,
+            merge_points: false,
+            warmup: 0,
+            paused_at: None,
+            clock,
+            counters: BTreeMap::new(),
+            labels: Labels::new(),
+            sessions: session::current(),
+            collector: collector::current(),
+            depth_base: None,
+            max_depth: 0,
+            live_id: None,
+            deadline: None,
+            slower_than: None,
+            trace: None,
+            correlation_id: None,
+            service_started: false,
+            timers: Timers::default(),
+        }
+    }
+
+    /// Register this execution as alive, for `flush_live` when live tracking is enabled
+    /// and for the enforcement of its deadline
+    pub fn track_live(&mut self) {
+        if self.deadline.is_none() && !live::tracking() {
+            return;
+        }
+        let begin = std::time::Instant::now();
+        self.live_id = Some(live::register(Live {
+            name: self.name.to_string(),
+            labels: self.labels.clone(),
+            clock: self.clock,
+            begin: self.begin_timestamp,
+            paused_at: self.paused_at,
+            deadline: self.deadline,
+        }));
+        overhead::add(begin);
+    }
+
+    fn update_live(&self) {
+        if let Some(id) = self.live_id {
+            live::update(id, self.begin_timestamp, self.paused_at);
+        }
+    }
+
+    /// Data of an execution that may move between threads, the nesting depth and the idle time are not observed
+    pub fn detached(name: &str) -> Self {
+        Self::unbound(name, Clock::default())
+    }
+
+    fn current(&self) -> SystemTime {
+        self.paused_at.unwrap_or_else(|| self.clock.now())
+    }
+
+    pub fn pause(&mut self) {
+        if self.paused_at.is_none() {
+            self.paused_at = Some(self.clock.now());
+            self.update_live();
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if let Some(paused_at) = self.paused_at.take() {
+            if let Ok(d) = self.clock.now().duration_since(paused_at) {
+                self.begin_timestamp += d;
+                self.now += d;
+            }
+            self.update_live();
         }
     }
 
     pub fn add_point(&mut self, name: &str) {
-        let now = std::time::SystemTime::now();
-        if let Ok(d) = now.duration_since(self.now) {
-            self.points.push(Point {
-                name: name.to_string(),
-                duration: d.as_nanos(),
-            });
+        let policy = clock::anomaly_policy();
+        if let Some((d, now)) = policy.elapsed(self.now, self.current()) {
+            self.push_point(name, d.as_nanos());
             self.now = now;
         }
     }
 
+    /// Add a point timed externally, the next point is measured from the end of this one
+    pub fn add_point_duration(&mut self, name: &str, duration: Duration) {
+        self.push_point(name, duration.as_nanos());
+        self.now += duration;
+    }
+
+    fn push_point(&mut self, name: &str, duration: DurationUnit) {
+        if !self.record_points {
+            return;
+        }
+        if self.merge_points {
+            if let Some(p) = self.points.iter_mut().find(|p| p.name == name) {
+                p.duration += duration;
+                p.hits += 1;
+                return;
+            }
+        }
+        self.points.push(Point {
+            name: name.to_string(),
+            duration,
+            hits: 1,
+        });
+    }
+
+    pub fn add_counter(&mut self, name: &str, value: u64) {
+        let counter = self.counters.entry(name.to_string()).or_insert(0);
+        *counter = counter.saturating_add(value);
+    }
+
+    /// End the wait phase of a queued task, the rest of the execution is its service phase
+    pub fn mark_service_start(&mut self) {
+        if !self.service_started {
+            self.add_point(WAIT_POINT);
+            self.service_started = true;
+        }
+    }
+
     pub fn stop(&mut self) {
-        if let Ok(d) = SystemTime::now().duration_since(self.begin_timestamp) {
-            self.duration = d.as_nanos();
-            ExecProbeManager::unsafe_report(self);
+        if self.service_started {
+            self.add_point(SERVICE_POINT);
+        }
+        match self.total(clock::anomaly_policy()) {
+            Some(d) => self.stop_with_duration(d.as_nanos()),
+            None => {
+                if let Some(id) = self.live_id.take() {
+                    live::unregister(id);
+                }
+            }
+        }
+    }
+
+    /// Time elapsed since the creation, `None` when discarded by the policy
+    fn total(&self, policy: ClockAnomalyPolicy) -> Option<Duration> {
+        let mut end = self.current();
+        if policy == ClockAnomalyPolicy::Saturate {
+            end = end.max(self.now);
         }
+        policy.elapsed(self.begin_timestamp, end).map(|(d, _)| d)
+    }
+
+    pub fn stop_with_duration(&mut self, duration: DurationUnit) {
+        let begin = std::time::Instant::now();
+        self.duration = duration;
+        let timers = self.timers.take();
+        if self.record_points {
+            self.points.extend(timers);
+        }
+        if let Some(base) = self.depth_base.take() {
+            self.max_depth = depth::end(base);
+            gap::stopped();
+        }
+        if let Some(id) = self.live_id {
+            live::unregister(id);
+        }
+        ExecProbeManager::global_report(self);
+        self.live_id = None;
+        overhead::add(begin);
     }
 }
 
+/// Point of a single execution, as added by `ExecProbe::add_point`
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::Point;
+/// use std::time::Duration;
+///
+/// exec_duration::on_report(|name: &str, total: Duration, points: &[Point]| {
+///     for p in points.iter() {
+///         println!("[{}::{}] costs {:?}", name, p.get_name(), p.get_duration());
+///     }
+/// });
+/// ```
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Point {
-    pub name: String,
-    pub duration: DurationUnit,
+    pub(crate) name: String,
+    pub(crate) duration: DurationUnit,
+    #[cfg_attr(feature = "serde", serde(default = "Point::single_hit"))]
+    pub(crate) hits: u64,
+}
+
+impl Point {
+    /// Create a new point, for instance to build a `RawEvent`
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration::Point;
+    /// use std::time::Duration;
+    ///
+    /// let p = Point::new("part 1", Duration::from_millis(3));
+    /// assert_eq!(p.get_duration(), Duration::from_millis(3));
+    /// ```
+    pub fn new(name: &str, duration: Duration) -> Self {
+        Point {
+            name: name.to_string(),
+            duration: duration.as_nanos(),
+            hits: 1,
+        }
+    }
+
+    /// Sum of the squared durations of the hits, each merged hit is assumed to last their average
+    pub(crate) fn sum_squares(&self) -> DurationUnit {
+        let avg = self.duration / self.hits.max(1) as DurationUnit;
+        (self.hits as DurationUnit).saturating_mul(avg.saturating_mul(avg))
+    }
+
+    #[cfg(feature = "serde")]
+    fn single_hit() -> u64 {
+        1
+    }
+
+    /// Get point name
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::Point;
+    /// use std::time::Duration;
+    ///
+    /// exec_duration::on_report(|name: &str, _: Duration, points: &[Point]| {
+    ///     for p in points.iter() {
+    ///         println!("[{}::{}]", name, p.get_name());
+    ///     }
+    /// });
+    /// ```
+    pub fn get_name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Get the time elapsed since the previous point (or the probe creation)
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::Point;
+    /// use std::time::Duration;
+    ///
+    /// exec_duration::on_report(|name: &str, _: Duration, points: &[Point]| {
+    ///     for p in points.iter() {
+    ///         println!("[{}::{}] costs {:?}", name, p.get_name(), p.get_duration());
+    ///     }
+    /// });
+    /// ```
+    pub fn get_duration(&self) -> Duration {
+        Duration::from_nanos(self.duration as u64)
+    }
+
+    /// Get the number of `add_point` calls merged into this point, 1 unless the probe merges its points
+    /// (see `ExecProbeBuilder::merge_points`), in which case the duration is their sum
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::builder("main").merge_points(true).build();
+    /// for _ in 0..3 {
+    ///     ep.add_point("iter");
+    /// }
+    /// assert_eq!(ep.points()[0].get_hits(), 3);
+    /// ```
+    pub fn get_hits(&self) -> u64 {
+        self.hits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::clock;
+    use crate::clock::ClockAnomalyPolicy;
+    use crate::config::Config;
+    use crate::manager::{ExecData, ExecProbeManager};
+    use crate::output::DurationUnit;
+    use std::time::Duration;
+
+    #[test]
+    fn zero_duration_count() {
+        let mut ctx = ExecProbeManager::new(Config::default());
+        for d in [0, 10, 0] {
+            let mut data = ExecData::new("main");
+            data.add_point("part 1");
+            data.duration = d;
+            ctx.report(&mut data);
+        }
+
+        let list = ctx.fetch_results();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].get_exec_count(), 3);
+        assert_eq!(list[0].get_zero_duration_count(), 2);
+        assert_eq!(list[0].get_exec_percent(), 100);
+    }
+
+    #[test]
+    fn coefficient_of_variation() {
+        let mut ctx = ExecProbeManager::new(Config::default());
+        for d in [2, 4, 4, 4, 5, 5, 7, 9] {
+            let mut data = ExecData::new("main");
+            data.add_point("part 1");
+            data.duration = d;
+            ctx.report(&mut data);
+        }
+
+        // mean 5, standard deviation 2
+        let list = ctx.fetch_results();
+        assert!((list[0].get_cv().unwrap() - 0.4).abs() < 1e-9);
+        assert!(list[0].get_elements()[0].get_variance().is_some());
+    }
+
+    #[test]
+    fn max_probes_keeps_sessions() {
+        let config = Config {
+            max_probes: Some(1),
+            ..Config::default()
+        };
+        let mut ctx = ExecProbeManager::new(config);
+        for name in ["first", "second"] {
+            let mut data = ExecData::new(name);
+            data.sessions = vec![String::from("startup")];
+            ctx.report(&mut data);
+        }
+
+        let list = ctx.fetch_results();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].get_name(), "first");
+        assert_eq!(ctx.fetch_session_results("startup").len(), 2);
+    }
+
+    #[test]
+    fn exponential_moving_average() {
+        let mut ctx = ExecProbeManager::new(Config::default());
+        ctx.set_ema_alpha(0.5);
+        for d in [100, 200, 400] {
+            let mut data = ExecData::new("main");
+            data.duration = d;
+            ctx.report(&mut data);
+        }
+
+        // 100, then 150, then 275
+        let list = ctx.fetch_results();
+        assert_eq!(list[0].get_ema(), Some(Duration::from_nanos(275)));
+    }
+
+    #[test]
+    fn jitter() {
+        let mut ctx = ExecProbeManager::new(Config::default());
+        for d in [40, 10, 70, 25] {
+            let mut data = ExecData::new("main");
+            data.add_point("part 1");
+            data.duration = d;
+            ctx.report(&mut data);
+        }
+
+        let list = ctx.fetch_results();
+        assert_eq!(list[0].get_min_duration(), Some(Duration::from_nanos(10)));
+        assert_eq!(list[0].get_max_duration(), Some(Duration::from_nanos(70)));
+        assert_eq!(list[0].get_jitter(), Duration::from_nanos(60));
+        assert_eq!(list[0].get_elements()[0].get_jitter(), Duration::ZERO);
+    }
+
+    #[test]
+    fn clock_anomaly_policy() {
+        let hour = Duration::from_secs(3600);
+        for policy in [
+            ClockAnomalyPolicy::Drop,
+            ClockAnomalyPolicy::Zero,
+            ClockAnomalyPolicy::Saturate,
+        ] {
+            clock::set_anomaly_policy(policy);
+            let mut data = ExecData::detached("main");
+            // the clock goes one hour backward after the probe creation
+            let ahead = data.now + hour;
+            data.begin_timestamp = ahead;
+            data.now = ahead;
+            data.add_point("part 1");
+            let points: Vec<DurationUnit> = data.points.iter().map(|p| p.duration).collect();
+            let total = data.total(policy);
+            match policy {
+                ClockAnomalyPolicy::Drop => {
+                    assert!(points.is_empty());
+                    assert_eq!(data.now, ahead);
+                    assert_eq!(total, None);
+                }
+                ClockAnomalyPolicy::Zero => {
+                    assert_eq!(points, vec![0]);
+                    assert!(data.now < ahead);
+                    assert_eq!(total, Some(Duration::ZERO));
+                }
+                ClockAnomalyPolicy::Saturate => {
+                    assert_eq!(points, vec![0]);
+                    assert_eq!(data.now, ahead);
+                    assert_eq!(total, Some(Duration::ZERO));
+                }
+            }
+        }
+        clock::set_anomaly_policy(ClockAnomalyPolicy::Drop);
+
+        // once points are recorded, a saturated total keeps the time measured so far
+        let mut data = ExecData::detached("main");
+        data.begin_timestamp += hour;
+        data.now = data.begin_timestamp + hour;
+        assert_eq!(data.total(ClockAnomalyPolicy::Saturate), Some(hour));
+        assert_eq!(data.total(ClockAnomalyPolicy::Drop), None);
+    }
 }
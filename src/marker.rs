@@ -0,0 +1,21 @@
+use rustc_hash::FxHashMap as HashMap;
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+thread_local! {
+    static MARKS: RefCell<HashMap<String, Instant>> = RefCell::new(HashMap::default());
+}
+
+/// Record the current time under `name` for the current thread, replacing a previous mark of the same name
+pub(crate) fn mark(name: &str) {
+    let now = Instant::now();
+    MARKS.with(|m| m.borrow_mut().insert(name.to_string(), now));
+}
+
+/// Time elapsed from mark `a` to mark `b` of the current thread, `None` if one is missing or `b` precedes `a`
+pub(crate) fn between(a: &str, b: &str) -> Option<Duration> {
+    MARKS.with(|m| {
+        let marks = m.borrow();
+        marks.get(b)?.checked_duration_since(*marks.get(a)?)
+    })
+}
@@ -0,0 +1,72 @@
+use crate::manager::ExecData;
+
+/// Execution probe suited to async code.
+/// Instances are created using `exec_duration::measure_async` function, the wall time elapsed since the creation
+/// is reported when the guard goes out of scope. Unlike `ExecProbe`, the guard can be held across await points
+/// and resumed on another thread, hence the nesting depth is not observed.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+///
+/// async fn handler() {
+///     let mut m = exec_duration::measure_async("handler");
+///     // read().await;
+///     m.checkpoint("read");
+///     // write().await;
+///     m.checkpoint("write");
+/// }
+/// ```
+#[derive(Debug)]
+#[must_use = "the measurement is reported as soon as the guard is dropped"]
+pub struct AsyncMeasure {
+    data: ExecData,
+    stop_done: bool,
+}
+
+impl AsyncMeasure {
+    pub(crate) fn new(name: &str) -> Self {
+        let mut data = ExecData::detached(name);
+        data.track_live();
+        AsyncMeasure {
+            data,
+            stop_done: false,
+        }
+    }
+
+    /// Add a new point, measuring the wall time elapsed since the previous one
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    ///
+    /// let mut m = exec_duration::measure_async("handler");
+    /// m.checkpoint("read");
+    /// ```
+    pub fn checkpoint(&mut self, name: &str) {
+        self.data.add_point(name);
+    }
+
+    /// Stop metrics and commit, this is done automatically when the guard goes out of scope
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    ///
+    /// let mut m = exec_duration::measure_async("handler");
+    /// m.checkpoint("read");
+    /// m.stop();
+    /// ```
+    pub fn stop(&mut self) {
+        if !self.stop_done {
+            self.data.stop();
+            self.stop_done = true;
+        }
+    }
+}
+
+impl Drop for AsyncMeasure {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
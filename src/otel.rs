@@ -0,0 +1,21 @@
+use crate::output::ExecDuration;
+use opentelemetry::metrics::Meter;
+use opentelemetry::KeyValue;
+
+const POINT_KEY: &str = "point";
+
+pub(crate) fn export(meter: &Meter, results: &[ExecDuration]) {
+    for r in results.iter() {
+        let histogram = meter
+            .f64_histogram(r.get_name().to_string())
+            .with_unit("s")
+            .build();
+        histogram.record(r.get_total_duration().as_secs_f64(), &[]);
+        for part in r.get_elements().iter() {
+            histogram.record(
+                part.get_total_duration().as_secs_f64(),
+                &[KeyValue::new(POINT_KEY, part.get_name().to_string())],
+            );
+        }
+    }
+}
@@ -1,7 +1,37 @@
+use crate::aggregator::CustomValue;
+#[cfg(feature = "tdigest")]
+use crate::digest::Percentiles;
+use crate::histogram::Histogram;
+use crate::ValidationError;
+use std::any::Any;
+use std::collections::BTreeMap;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::RwLock;
 use std::time::Duration;
 
-pub(crate) type DurationUnit = u128;
+/// Duration unit used for raw measurements (nanoseconds)
+pub type DurationUnit = u128;
+
+/// Name of the synthetic element accounting for the time elapsed between the last point of an execution and its end,
+/// so that the percentages of the elements of a probe sum to 100%
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::output;
+///
+/// let list = exec_duration::fetch_results();
+/// for r in list.iter() {
+///     if let Some(tail) = r.get_elements().iter().find(|e| e.get_name() == output::TAIL) {
+///         println!("[{}] {}% after the last point", r.get_name(), tail.get_exec_percent());
+///     }
+/// }
+/// ```
+pub const TAIL: &str = "<tail>";
 
 /// Execution duration metrics
 ///
@@ -31,28 +61,311 @@ pub(crate) type DurationUnit = u128;
 pub struct ExecDuration {
     name: String,
     count: u64,
+    zero_count: u64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    clamped_count: u64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    fast_count: u64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    provisional_count: u64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    sum_squares: Option<DurationUnit>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    min_duration: Option<DurationUnit>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    max_duration: Option<DurationUnit>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    max_depth: usize,
+    #[cfg_attr(feature = "serde", serde(default))]
+    ema: Option<DurationUnit>,
     duration: DurationUnit,
     total: DurationUnit,
+    #[cfg_attr(feature = "serde", serde(default))]
+    parent_count: u64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    depth: usize,
+    #[cfg_attr(feature = "serde", serde(default))]
+    labels: BTreeMap<String, String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    counters: BTreeMap<String, u64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    budget: Option<DurationUnit>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    histogram: Option<Histogram>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    samples: Vec<DurationUnit>,
     childs: Vec<ExecDuration>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    custom: Option<CustomValue>,
+    #[cfg(feature = "tdigest")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    percentiles: Option<Percentiles>,
 }
 
 impl ExecDuration {
+    /// Create a new builder, to construct execution metrics by hand (tests, importers, ...)
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration::output::ExecDuration;
+    /// use std::time::Duration;
+    ///
+    /// let part = ExecDuration::builder("part 1")
+    ///     .count(2)
+    ///     .total(Duration::from_millis(20))
+    ///     .build()
+    ///     .unwrap();
+    /// let main = ExecDuration::builder("main")
+    ///     .count(2)
+    ///     .total(Duration::from_millis(50))
+    ///     .child(part)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(main.get_elements()[0].get_exec_percent(), 40);
+    /// ```
+    pub fn builder(name: &str) -> ExecDurationBuilder {
+        ExecDurationBuilder {
+            name: name.to_string(),
+            count: 0,
+            total: Duration::default(),
+            childs: Vec::new(),
+        }
+    }
+
+    /// Create metrics without elements, executed `count` times for `total_nanos` nanoseconds in total.
+    /// Unlike `builder`, nothing is validated, which makes it handy to build expected trees in tests,
+    /// for instance zero-duration ones to compare with `exec_duration::fetch_results_normalized`.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration::output::ExecDuration;
+    /// use std::time::Duration;
+    ///
+    /// let part = ExecDuration::leaf("part 1", 2, 1_000);
+    /// assert_eq!(part.get_total_duration(), Duration::from_micros(1));
+    /// ```
+    pub fn leaf(name: &str, count: u64, total_nanos: u64) -> ExecDuration {
+        ExecDuration::new(
+            name,
+            count,
+            total_nanos as DurationUnit,
+            total_nanos as DurationUnit,
+        )
+    }
+
+    /// Create metrics executed `count` times for `total_nanos` nanoseconds in total, with the given elements.
+    /// The percentages of the elements are computed against `total_nanos`, see `leaf`.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration::output::ExecDuration;
+    ///
+    /// let main = ExecDuration::branch(
+    ///     "main",
+    ///     1,
+    ///     1_000,
+    ///     vec![ExecDuration::leaf("part 1", 1, 250), ExecDuration::leaf("part 2", 1, 750)],
+    /// );
+    /// assert_eq!(main.get_elements()[1].get_exec_percent(), 75);
+    /// ```
+    pub fn branch(
+        name: &str,
+        count: u64,
+        total_nanos: u64,
+        children: Vec<ExecDuration>,
+    ) -> ExecDuration {
+        let mut elt = ExecDuration::leaf(name, count, total_nanos);
+        for mut child in children.into_iter() {
+            child.set_total(total_nanos as DurationUnit);
+            elt.add(child);
+        }
+        elt
+    }
+
     #[doc(hidden)]
     pub(crate) fn new(name: &str, count: u64, duration: DurationUnit, total: DurationUnit) -> Self {
         ExecDuration {
             name: name.to_string(),
             count,
+            zero_count: 0,
+            clamped_count: 0,
+            fast_count: 0,
+            provisional_count: 0,
+            sum_squares: None,
+            min_duration: None,
+            max_duration: None,
+            max_depth: 0,
+            ema: None,
             duration,
             total,
+            parent_count: count,
+            depth: 0,
+            labels: BTreeMap::new(),
+            counters: BTreeMap::new(),
+            budget: None,
+            histogram: None,
+            samples: Vec::new(),
             childs: Vec::new(),
+            custom: None,
+            #[cfg(feature = "tdigest")]
+            percentiles: None,
         }
     }
 
     #[doc(hidden)]
-    pub(crate) fn add(&mut self, v: ExecDuration) {
+    pub(crate) fn set_custom(&mut self, custom: Option<CustomValue>) {
+        self.custom = custom;
+    }
+
+    #[doc(hidden)]
+    pub(crate) fn add(&mut self, mut v: ExecDuration) {
+        v.set_depth(self.depth + 1);
+        v.parent_count = self.count;
         self.childs.push(v);
     }
 
+    fn set_depth(&mut self, depth: usize) {
+        self.depth = depth;
+        for child in self.childs.iter_mut() {
+            child.set_depth(depth + 1);
+        }
+    }
+
+    #[doc(hidden)]
+    pub(crate) fn set_labels(&mut self, labels: BTreeMap<String, String>) {
+        self.labels = labels;
+    }
+
+    #[doc(hidden)]
+    pub(crate) fn set_counters(&mut self, counters: BTreeMap<String, u64>) {
+        self.counters = counters;
+    }
+
+    #[cfg(feature = "tdigest")]
+    #[doc(hidden)]
+    pub(crate) fn set_percentiles(&mut self, percentiles: Option<Percentiles>) {
+        self.percentiles = percentiles;
+    }
+
+    #[doc(hidden)]
+    pub(crate) fn set_histogram(&mut self, histogram: Option<Histogram>) {
+        self.histogram = histogram;
+    }
+
+    #[doc(hidden)]
+    pub(crate) fn set_samples(&mut self, samples: Vec<DurationUnit>) {
+        self.samples = samples;
+    }
+
+    #[doc(hidden)]
+    pub(crate) fn set_budget(&mut self, budget: Option<DurationUnit>) {
+        self.budget = budget;
+    }
+
+    #[doc(hidden)]
+    pub(crate) fn set_ema(&mut self, ema: Option<DurationUnit>) {
+        self.ema = ema;
+    }
+
+    #[doc(hidden)]
+    pub(crate) fn merge(&mut self, other: ExecDuration) {
+        for (name, value) in other.counters.into_iter() {
+            let counter = self.counters.entry(name).or_insert(0);
+            *counter = counter.saturating_add(value);
+        }
+        self.ema = match (self.ema, other.ema) {
+            // weighted by the executions behind each side
+            (Some(a), Some(b)) if self.count + other.count > 0 => {
+                let count = (self.count + other.count) as DurationUnit;
+                Some((a * self.count as DurationUnit + b * other.count as DurationUnit) / count)
+            }
+            (a, b) => a.or(b),
+        };
+        self.count += other.count;
+        self.zero_count += other.zero_count;
+        self.clamped_count += other.clamped_count;
+        self.fast_count += other.fast_count;
+        self.provisional_count += other.provisional_count;
+        self.sum_squares = match (self.sum_squares, other.sum_squares) {
+            (Some(a), Some(b)) => Some(a.saturating_add(b)),
+            _ => None,
+        };
+        self.min_duration = match (self.min_duration, other.min_duration) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.max_duration = self.max_duration.max(other.max_duration);
+        self.max_depth = self.max_depth.max(other.max_depth);
+        self.budget = self.budget.or(other.budget);
+        self.histogram = match (self.histogram.take(), other.histogram) {
+            // counts over different buckets can not be combined
+            (Some(mut a), Some(b)) => a.merge(&b).then_some(a),
+            (a, b) => a.or(b),
+        };
+        self.samples.extend(other.samples);
+        #[cfg(feature = "tdigest")]
+        {
+            self.percentiles = match (self.percentiles.take(), other.percentiles) {
+                (Some(a), Some(b)) => Some(a.merge(&b)),
+                (a, b) => a.or(b),
+            };
+        }
+        self.duration += other.duration;
+        self.total += other.total;
+        for child in other.childs.into_iter() {
+            match self.childs.iter_mut().find(|c| c.name == child.name) {
+                Some(c) => c.merge(child),
+                None => self.childs.push(child),
+            }
+        }
+        let total = self.duration;
+        for child in self.childs.iter_mut() {
+            child.set_total(total);
+            child.parent_count = self.count;
+        }
+    }
+
+    #[doc(hidden)]
+    pub(crate) fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
+    #[doc(hidden)]
+    pub(crate) fn set_zero_count(&mut self, zero_count: u64) {
+        self.zero_count = zero_count;
+    }
+
+    #[doc(hidden)]
+    pub(crate) fn set_clamped_count(&mut self, clamped_count: u64) {
+        self.clamped_count = clamped_count;
+    }
+
+    #[doc(hidden)]
+    pub(crate) fn set_fast_count(&mut self, fast_count: u64) {
+        self.fast_count = fast_count;
+    }
+
+    #[doc(hidden)]
+    pub(crate) fn set_provisional_count(&mut self, provisional_count: u64) {
+        self.provisional_count = provisional_count;
+    }
+
+    #[doc(hidden)]
+    pub(crate) fn set_min_max(&mut self, min: Option<DurationUnit>, max: Option<DurationUnit>) {
+        self.min_duration = min;
+        self.max_duration = max;
+    }
+
+    #[doc(hidden)]
+    pub(crate) fn set_sum_squares(&mut self, sum_squares: Option<DurationUnit>) {
+        self.sum_squares = sum_squares;
+    }
+
+    #[doc(hidden)]
+    pub(crate) fn set_total(&mut self, total: DurationUnit) {
+        self.total = total;
+    }
+
     /// Get execution duration as a percentage
     /// # Examples
     /// ```
@@ -70,10 +383,60 @@ impl ExecDuration {
     /// }
     /// ```
     pub fn get_exec_percent(&self) -> u8 {
+        if self.total == 0 {
+            return 0;
+        }
         (self.duration * 100 / self.total) as u8
     }
 
-    /// Get execution count
+    /// Check the invariants of these metrics and of their elements, typically after loading a report from an
+    /// untrusted source. Elements may overlap (see `ExecProbe::timer`), so only each of them is checked against
+    /// its parent, not their sum.
+    ///
+    /// # Errors
+    ///
+    /// - `ValidationError::ZeroCount` if a node has a duration but no execution.
+    /// - `ValidationError::CountMismatch` if a node has more zero-duration or provisional executions than executions.
+    /// - `ValidationError::RangeMismatch` if the shortest or longest execution time of a node is out of its range.
+    /// - `ValidationError::ChildExceedsParent` if an element lasts longer than its parent.
+    /// - `ValidationError::TotalMismatch` if the reference total of an element is not the duration of its parent.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    ///
+    /// for r in exec_duration::fetch_results().iter() {
+    ///     assert!(r.validate().is_ok());
+    /// }
+    /// ```
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let name = || self.name.to_string();
+        if self.count == 0 && self.duration > 0 {
+            return Err(ValidationError::ZeroCount(name()));
+        }
+        // clamped executions are not part of the count
+        if self.zero_count > self.count || self.provisional_count > self.count {
+            return Err(ValidationError::CountMismatch(name()));
+        }
+        if let (Some(min), Some(max)) = (self.min_duration, self.max_duration) {
+            if min > max || max > self.duration {
+                return Err(ValidationError::RangeMismatch(name()));
+            }
+        }
+        for child in self.childs.iter() {
+            if child.duration > self.duration {
+                return Err(ValidationError::ChildExceedsParent(child.name.to_string()));
+            }
+            if child.total != self.duration {
+                return Err(ValidationError::TotalMismatch(child.name.to_string()));
+            }
+            child.validate()?;
+        }
+        Ok(())
+    }
+
+    /// Get the execution duration as a percentage of the given total, typically the one of an ancestor,
+    /// to compute arbitrary relative shares in nested trees. Returns 0 when the given total is zero.
     ///
     /// # Examples
     /// ```
@@ -86,15 +449,24 @@ impl ExecDuration {
     ///
     /// // fetch results
     /// let list = exec_duration::fetch_results();
+    /// let total = list.iter().map(|r| r.get_total_duration()).sum();
     /// for r in list.iter() {
-    ///     println!("[{}] was executed {} times", r.get_name(), r.get_exec_count());
+    ///     for part in r.get_elements().iter() {
+    ///         println!("[{}::{}] {:.1}% of all probes", r.get_name(), part.get_name(), part.percent_of(total));
+    ///     }
     /// }
     /// ```
-    pub fn get_exec_count(&self) -> u64 {
-        self.count
+    pub fn percent_of(&self, ancestor_total: Duration) -> f64 {
+        let total = ancestor_total.as_nanos();
+        if total == 0 {
+            return 0.0;
+        }
+        self.duration as f64 * 100.0 / total as f64
     }
 
-    /// Get average execution time
+    /// Get the average execution time of an element as a percentage of the average execution time of its probe,
+    /// 100 for a probe. Unlike `get_exec_percent`, an element not reached by every execution is not diluted.
+    /// Returns 0 when the average of the probe is zero, and saturates at 255.
     ///
     /// # Examples
     /// ```
@@ -108,16 +480,23 @@ impl ExecDuration {
     /// // fetch results
     /// let list = exec_duration::fetch_results();
     /// for r in list.iter() {
-    ///     println!("[{}] costs ~{} seconds in average",
-    ///         r.get_name(), r.get_avg_duration().as_secs()
-    ///     );
+    ///     for part in r.get_elements().iter() {
+    ///         println!("[{}::{}] {}% of a typical run", r.get_name(), part.get_name(), part.get_avg_percent());
+    ///     }
     /// }
     /// ```
-    pub fn get_avg_duration(&self) -> Duration {
-        Duration::from_nanos((self.duration / self.count as DurationUnit) as u64)
+    pub fn get_avg_percent(&self) -> u8 {
+        if self.count == 0 || self.total == 0 || self.parent_count == 0 {
+            return 0;
+        }
+        let percent = self.duration * self.parent_count as DurationUnit * 100
+            / (self.count as DurationUnit * self.total);
+        percent.min(u8::MAX as DurationUnit) as u8
     }
 
-    /// Get total execution time
+    /// Get the fraction of the executions of its probe reaching an element, 1.0 for a probe.
+    /// A point inside a branch taken 30% of the time reports 0.3; points reached several times per execution
+    /// are capped at 1.0. Returns 0.0 when the probe has no execution.
     ///
     /// # Examples
     /// ```
@@ -131,14 +510,22 @@ impl ExecDuration {
     /// // fetch results
     /// let list = exec_duration::fetch_results();
     /// for r in list.iter() {
-    ///     println!("[{}] costs {} seconds", r.get_name(), r.get_total_duration().as_secs());
+    ///     for part in r.get_elements().iter() {
+    ///         println!("[{}::{}] reached by {:.0}% of the runs", r.get_name(), part.get_name(), part.get_hit_rate() * 100.0);
+    ///     }
     /// }
     /// ```
-    pub fn get_total_duration(&self) -> Duration {
-        Duration::from_nanos(self.duration as u64)
+    pub fn get_hit_rate(&self) -> f64 {
+        if self.parent_count == 0 {
+            return 0.0;
+        }
+        (self.count as f64 / self.parent_count as f64).min(1.0)
     }
 
-    /// Get elements if any
+    /// Get execution duration as an unrounded percentage
+    ///
+    /// Unlike `get_exec_percent`, small contributors (below 1%) remain visible.
+    /// Returns 0 when the reference total duration is zero.
     ///
     /// # Examples
     /// ```
@@ -153,18 +540,20 @@ impl ExecDuration {
     /// let list = exec_duration::fetch_results();
     /// for r in list.iter() {
     ///     for part in r.get_elements().iter() {
-    ///         println!("[{}::{}] costs {} seconds ({}%)",
-    ///             r.get_name(), part.get_name(),
-    ///             part.get_total_duration().as_secs(), part.get_exec_percent()
+    ///         println!("Exec duration [{}::{}] {:.2}%",
+    ///             r.get_name(), part.get_name(), part.get_exec_percent_f64()
     ///         );
     ///     }
     /// }
     /// ```
-    pub fn get_elements(&self) -> &[ExecDuration] {
-        &self.childs
+    pub fn get_exec_percent_f64(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.duration as f64 * 100.0 / self.total as f64
     }
 
-    /// Get measured code block name
+    /// Get execution count
     ///
     /// # Examples
     /// ```
@@ -178,32 +567,2254 @@ impl ExecDuration {
     /// // fetch results
     /// let list = exec_duration::fetch_results();
     /// for r in list.iter() {
-    ///     println!("[{}] costs {} seconds (~{} seconds in average)",
-    ///         r.get_name(), r.get_total_duration().as_secs(),
-    ///         r.get_avg_duration().as_secs()
-    ///     );
+    ///     println!("[{}] was executed {} times", r.get_name(), r.get_exec_count());
     /// }
     /// ```
-    pub fn get_name(&self) -> &str {
-        self.name.as_str()
+    pub fn get_exec_count(&self) -> u64 {
+        self.count
     }
-}
 
-impl fmt::Display for ExecDuration {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(
-            f,
-            "[{}] {}% Call: {:?} T: {:?} Avg: {:?}",
-            self.get_name(),
-            self.get_exec_percent(),
-            self.get_exec_count(),
-            self.get_total_duration(),
-            self.get_avg_duration(),
-        )?;
-        for v in self.childs.iter() {
-            write!(f, "[{}] {}", self.name, v)?;
-        }
+    /// Get the number of executions measured as zero nanoseconds
+    ///
+    /// A non-zero value means that the measured block is too fine-grained to be measured.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    ///
+    /// // code
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter() {
+    ///     if r.get_zero_duration_count() > 0 {
+    ///         println!("[{}] is too fine-grained to be measured", r.get_name());
+    ///     }
+    /// }
+    /// ```
+    pub fn get_zero_duration_count(&self) -> u64 {
+        self.zero_count
+    }
 
-        Ok(())
+    /// Get the number of executions discarded for exceeding the clamp set using `exec_duration::set_sample_clamp`.
+    /// These executions are not part of the count nor of the durations.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    ///
+    /// // code
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter() {
+    ///     if r.get_clamped_count() > 0 {
+    ///         println!("[{}] {} executions were discarded", r.get_name(), r.get_clamped_count());
+    ///     }
+    /// }
+    /// ```
+    pub fn get_clamped_count(&self) -> u64 {
+        self.clamped_count
+    }
+
+    /// Get the number of executions discarded for not exceeding the threshold set using
+    /// `ExecProbeBuilder::record_if_slower_than`. These executions are not part of the count nor of the durations.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    /// use std::time::Duration;
+    ///
+    /// let mut ep = ExecProbe::builder("main").record_if_slower_than(Duration::from_millis(100)).build();
+    ///
+    /// // code
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter() {
+    ///     println!("[{}] {} slow executions out of {}", r.get_name(), r.get_exec_count(), r.get_exec_count() + r.get_fast_count());
+    /// }
+    /// ```
+    pub fn get_fast_count(&self) -> u64 {
+        self.fast_count
+    }
+
+    /// Get the number of executions still in progress when `exec_duration::flush_live` was called.
+    /// These executions are part of the count and of the durations with their elapsed time at that moment.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let ep = ExecProbe::new("main");
+    /// exec_duration::flush_live();
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter() {
+    ///     println!("[{}] {} executions in progress", r.get_name(), r.get_provisional_count());
+    /// }
+    /// ```
+    pub fn get_provisional_count(&self) -> u64 {
+        self.provisional_count
+    }
+
+    /// Get the exponential moving average of the execution durations, `None` unless enabled with
+    /// `exec_duration::set_ema_alpha`. Unlike the average, it is kept by `exec_duration::reset_counts`.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// exec_duration::set_ema_alpha(0.1);
+    /// let mut ep = ExecProbe::new("main");
+    ///
+    /// // code
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter() {
+    ///     println!("[{}] recent average: {:?}", r.get_name(), r.get_ema());
+    /// }
+    /// ```
+    pub fn get_ema(&self) -> Option<Duration> {
+        self.ema.map(|e| Duration::from_nanos(e as u64))
+    }
+
+    /// Get the deepest nesting depth, as tracked by `exec_duration::depth_guard`, observed during the executions
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    ///
+    /// // code
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter() {
+    ///     println!("[{}] max depth: {}", r.get_name(), r.get_max_depth());
+    /// }
+    /// ```
+    pub fn get_max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Get average execution time
+    ///
+    /// Returns 0 when there is no execution.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    ///
+    /// // code
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter() {
+    ///     println!("[{}] costs ~{} seconds in average",
+    ///         r.get_name(), r.get_avg_duration().as_secs()
+    ///     );
+    /// }
+    /// ```
+    pub fn get_avg_duration(&self) -> Duration {
+        if self.count == 0 {
+            return Duration::default();
+        }
+        Duration::from_nanos((self.duration / self.count as DurationUnit) as u64)
+    }
+
+    /// Get the shortest execution time.
+    /// Returns `None` if it is not tracked, which is the case for elements and fast probes.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    ///
+    /// // code
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter() {
+    ///     println!("[{}] min: {:?}", r.get_name(), r.get_min_duration());
+    /// }
+    /// ```
+    pub fn get_min_duration(&self) -> Option<Duration> {
+        self.min_duration.map(|d| Duration::from_nanos(d as u64))
+    }
+
+    /// Get the longest execution time.
+    /// Returns `None` if it is not tracked, which is the case for elements and fast probes.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    ///
+    /// // code
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter() {
+    ///     println!("[{}] max: {:?}", r.get_name(), r.get_max_duration());
+    /// }
+    /// ```
+    pub fn get_max_duration(&self) -> Option<Duration> {
+        self.max_duration.map(|d| Duration::from_nanos(d as u64))
+    }
+
+    /// Generate `count` approximate execution times, for charting when only the aggregates are available.
+    /// The samples are evenly spread around the average, as widely as the shortest and longest execution times
+    /// allow while keeping the average; they all equal the average when the extremes are not tracked.
+    /// This is an approximation: the actual distribution is unknown.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    ///
+    /// // code
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter() {
+    ///     let samples = r.synthetic_samples();
+    ///     assert_eq!(samples.len() as u64, r.get_exec_count());
+    /// }
+    /// ```
+    pub fn synthetic_samples(&self) -> Vec<Duration> {
+        let avg = self.get_avg_duration().as_nanos() as i128;
+        let spread = match (self.min_duration, self.max_duration) {
+            (Some(min), Some(max)) => (avg - min as i128).min(max as i128 - avg).max(0),
+            _ => 0,
+        };
+        let n = self.count as i128;
+        (0..n)
+            .map(|i| {
+                // offsets are symmetric around the average, so that they sum to zero
+                let offset = if n > 1 {
+                    (2 * i - (n - 1)) * spread / (n - 1)
+                } else {
+                    0
+                };
+                Duration::from_nanos((avg + offset) as u64)
+            })
+            .collect()
+    }
+
+    /// Get the jitter of the execution time, the longest minus the shortest execution time.
+    /// A cheap stability indicator, zero when the extremes are not tracked.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    ///
+    /// // code
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter() {
+    ///     println!("[{}] jitter: {:?}", r.get_name(), r.get_jitter());
+    /// }
+    /// ```
+    pub fn get_jitter(&self) -> Duration {
+        match (self.get_min_duration(), self.get_max_duration()) {
+            (Some(min), Some(max)) => max.saturating_sub(min),
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// Get the variance of the execution time, in squared nanoseconds.
+    /// For an element, this is the variance of the time spent in the point each time it is reached.
+    /// Returns `None` if the variance is not tracked, which is the case for the tail element and fast probes,
+    /// or if there is no execution.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    ///
+    /// // code
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter() {
+    ///     for part in r.get_elements().iter() {
+    ///         if let Some(variance) = part.get_variance() {
+    ///             println!("[{}::{}] std dev: {:.0}ns", r.get_name(), part.get_name(), variance.sqrt());
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn get_variance(&self) -> Option<f64> {
+        let sum_squares = self.sum_squares?;
+        if self.count == 0 {
+            return None;
+        }
+        let count = self.count as f64;
+        let mean = self.duration as f64 / count;
+        Some((sum_squares as f64 / count - mean * mean).max(0.0))
+    }
+
+    /// Get the element with the highest variance, the main source of run-to-run instability of these metrics.
+    /// Returns `None` if no element tracks its variance, see `get_variance`.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    ///
+    /// // code
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter() {
+    ///     if let Some(part) = r.most_variable_element() {
+    ///         println!("[{}] is the least stable part of [{}]", part.get_name(), r.get_name());
+    ///     }
+    /// }
+    /// ```
+    pub fn most_variable_element(&self) -> Option<&ExecDuration> {
+        self.childs
+            .iter()
+            .filter_map(|c| c.get_variance().map(|v| (c, v)))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(c, _)| c)
+    }
+
+    /// Get the coefficient of variation of the execution time, the standard deviation divided by the average.
+    /// Returns `None` if the variance is not tracked, which is the case for the tail element and fast probes,
+    /// or if the average is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    ///
+    /// // code
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter() {
+    ///     if let Some(cv) = r.get_cv() {
+    ///         println!("[{}] cv: {:.2}", r.get_name(), cv);
+    ///     }
+    /// }
+    /// ```
+    pub fn get_cv(&self) -> Option<f64> {
+        let variance = self.get_variance()?;
+        if self.duration == 0 {
+            return None;
+        }
+        let mean = self.duration as f64 / self.count as f64;
+        Some(variance.sqrt() / mean)
+    }
+
+    /// Get the range `[lower, upper)` of the histogram bucket holding the most executions, the lowest one on ties.
+    /// This surfaces the dominant regime of a multimodal distribution (cache hit vs miss, ...) which the average hides.
+    /// Only available when the histogram mode is enabled, see `exec_duration::set_histogram`;
+    /// the upper bound of the last bucket is `Duration::MAX`.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use std::time::Duration;
+    ///
+    /// exec_duration::set_histogram(&[Duration::from_millis(1), Duration::from_millis(10)]);
+    ///
+    /// // code
+    ///
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter() {
+    ///     if let Some((lower, upper)) = r.get_mode_bucket() {
+    ///         println!("[{}] mostly between {:?} and {:?}", r.get_name(), lower, upper);
+    ///     }
+    /// }
+    /// ```
+    pub fn get_mode_bucket(&self) -> Option<(Duration, Duration)> {
+        let (lower, upper) = self.histogram.as_ref()?.mode()?;
+        let upper = upper.map_or(Duration::MAX, |u| Duration::from_nanos(u as u64));
+        Some((Duration::from_nanos(lower as u64), upper))
+    }
+
+    /// Get the average duration of the retained samples, without the `trim_percent` shortest and longest ones.
+    /// Unlike the plain average, a few extreme outliers (page faults, preemption, ...) barely move it.
+    /// Only available when the sample retention is enabled, see `exec_duration::set_sample_retention`;
+    /// `trim_percent` is clamped to `[0, 50)`, `None` when no sample is left.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// exec_duration::set_sample_retention(1000);
+    ///
+    /// // code
+    ///
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter() {
+    ///     if let Some(mean) = r.get_trimmed_mean(5.0) {
+    ///         println!("[{}] trimmed mean: {:?}", r.get_name(), mean);
+    ///     }
+    /// }
+    /// ```
+    pub fn get_trimmed_mean(&self, trim_percent: f64) -> Option<Duration> {
+        let mut samples = self.samples.clone();
+        samples.sort_unstable();
+        let trim = trim_percent.clamp(0.0, 50.0) / 100.0;
+        let dropped = (samples.len() as f64 * trim).floor() as usize;
+        let kept = samples.get(dropped..samples.len().saturating_sub(dropped))?;
+        if kept.is_empty() {
+            return None;
+        }
+        let mean = kept.iter().sum::<DurationUnit>() / kept.len() as DurationUnit;
+        Some(Duration::from_nanos(mean as u64))
+    }
+
+    /// Get a deep copy of these metrics with all durations, including the ones of the elements, multiplied by `factor`.
+    /// Counts and structure are preserved, percentile estimates are not part of the copy.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    ///
+    /// // code
+    ///
+    /// // normalize against a machine twice as fast
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter().map(|r| r.scaled(2.0)) {
+    ///     println!("{}", r);
+    /// }
+    /// ```
+    pub fn scaled(&self, factor: f64) -> ExecDuration {
+        let scale = |d: DurationUnit| (d as f64 * factor).max(0.0).round() as DurationUnit;
+        let mut r = self.clone();
+        r.duration = scale(self.duration);
+        r.total = scale(self.total);
+        r.sum_squares = self.sum_squares.map(|s| scale(scale(s)));
+        r.ema = self.ema.map(scale);
+        r.min_duration = self.min_duration.map(scale);
+        r.max_duration = self.max_duration.map(scale);
+        r.histogram = self.histogram.as_ref().map(|h| h.scaled(scale));
+        r.samples = self.samples.iter().map(|s| scale(*s)).collect();
+        #[cfg(feature = "tdigest")]
+        {
+            r.percentiles = None;
+        }
+        r.childs = self.childs.iter().map(|c| c.scaled(factor)).collect();
+        r
+    }
+
+    /// Get a deep copy of these metrics stripped of everything timing dependent, for snapshot tests:
+    /// durations are zeroed, the duration statistics (min, max, histogram, samples, custom aggregate, ...) removed
+    /// and the elements sorted by name at every level. Names, labels, counters and execution counts are preserved.
+    /// The `TAIL` element is dropped, it is only present when the tail lasts longer than zero nanoseconds.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    ///
+    /// // code
+    ///
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter().map(|r| r.normalized()) {
+    ///     println!("{}", r);
+    /// }
+    /// ```
+    pub fn normalized(&self) -> ExecDuration {
+        let mut r = self.clone();
+        r.duration = 0;
+        r.total = 0;
+        r.zero_count = 0;
+        r.clamped_count = 0;
+        r.fast_count = 0;
+        r.sum_squares = None;
+        r.min_duration = None;
+        r.max_duration = None;
+        r.ema = None;
+        r.histogram = None;
+        r.samples.clear();
+        r.custom = None;
+        #[cfg(feature = "tdigest")]
+        {
+            r.percentiles = None;
+        }
+        r.childs = self
+            .childs
+            .iter()
+            .filter(|c| c.name != TAIL)
+            .map(|c| c.normalized())
+            .collect();
+        r.childs.sort_by(|a, b| a.name.cmp(&b.name));
+        r
+    }
+
+    /// Get the top-line numbers of these metrics, without the elements
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    ///
+    /// // code
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_results();
+    /// for s in list.iter().map(|r| r.summary()) {
+    ///     println!("[{}] Call: {} Avg: {}ns", s.name, s.count, s.avg);
+    /// }
+    /// ```
+    pub fn summary(&self) -> Summary {
+        Summary {
+            name: self.name.to_string(),
+            count: self.count,
+            total: self.duration,
+            avg: self.get_avg_duration().as_nanos(),
+        }
+    }
+
+    /// Get total execution time
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    ///
+    /// // code
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter() {
+    ///     println!("[{}] costs {} seconds", r.get_name(), r.get_total_duration().as_secs());
+    /// }
+    /// ```
+    pub fn get_total_duration(&self) -> Duration {
+        Duration::from_nanos(self.duration as u64)
+    }
+
+    /// Get the estimated execution time at the given percentile, in [0, 100].
+    /// Percentiles are estimated from a t-digest kept per probe, which uses bounded memory whatever the number
+    /// of executions. Returns `None` for points and for results built by hand.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    ///
+    /// // code
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter() {
+    ///     println!("[{}] p99: {:?}", r.get_name(), r.get_percentile(99.0));
+    /// }
+    /// ```
+    #[cfg(feature = "tdigest")]
+    pub fn get_percentile(&self, p: f64) -> Option<Duration> {
+        let q = (p / 100.0).clamp(0.0, 1.0);
+        self.percentiles
+            .as_ref()
+            .and_then(|d| d.quantile(q))
+            .map(|d| Duration::from_nanos(d as u64))
+    }
+
+    /// Get throughput, the number of executions per second
+    ///
+    /// Returns 0 when there is no execution and infinity when executions were measured as zero nanoseconds.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    ///
+    /// // code
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter() {
+    ///     println!("[{}] {:.1} ops/s", r.get_name(), r.throughput());
+    /// }
+    /// ```
+    pub fn throughput(&self) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        if self.duration == 0 {
+            return f64::INFINITY;
+        }
+        self.count as f64 * 1_000_000_000.0 / self.duration as f64
+    }
+
+    /// Get exclusive (self) execution time, the total execution time minus the total execution time of the elements.
+    /// The `TAIL` element, the time after the last point, is part of the self time.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    ///
+    /// // code
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_grouped('.');
+    /// for r in list.iter() {
+    ///     println!("[{}] costs {} seconds by itself",
+    ///         r.get_name(), r.get_self_duration().as_secs()
+    ///     );
+    /// }
+    /// ```
+    pub fn get_self_duration(&self) -> Duration {
+        let childs: DurationUnit = self
+            .childs
+            .iter()
+            .filter(|c| c.name != TAIL)
+            .map(|c| c.duration)
+            .sum();
+        Duration::from_nanos(self.duration.saturating_sub(childs) as u64)
+    }
+
+    /// Get the labels of the aggregated series, as set by `ExecProbeBuilder::label`
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::builder("main").label("env", "prod").build();
+    /// ep.add_point("line 1");
+    /// ep.stop();
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter() {
+    ///     println!("[{}] {:?}", r.get_name(), r.get_labels());
+    /// }
+    /// ```
+    pub fn get_labels(&self) -> &BTreeMap<String, String> {
+        &self.labels
+    }
+
+    /// Get the counters accumulated with `ExecProbe::add_counter`, summed over all executions
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    /// ep.add_counter("bytes", 1024);
+    /// ep.add_point("line 1");
+    /// ep.stop();
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter() {
+    ///     if let Some(bytes) = r.get_counters().get("bytes") {
+    ///         let secs = r.get_total_duration().as_secs_f64();
+    ///         println!("[{}] {:.1} bytes/s", r.get_name(), *bytes as f64 / secs);
+    ///     }
+    /// }
+    /// ```
+    pub fn get_counters(&self) -> &BTreeMap<String, u64> {
+        &self.counters
+    }
+
+    /// Get the budget declared for the probe using `exec_duration::set_budget`, if any
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    /// use std::time::Duration;
+    ///
+    /// exec_duration::set_budget("render", Duration::from_millis(16));
+    /// let mut ep = ExecProbe::new("render");
+    /// ep.add_point("line 1");
+    /// ep.stop();
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter() {
+    ///     if let Some(budget) = r.budget() {
+    ///         println!("[{}] budget {:?}", r.get_name(), budget);
+    ///     }
+    /// }
+    /// ```
+    pub fn budget(&self) -> Option<Duration> {
+        self.budget.map(|b| Duration::from_nanos(b as u64))
+    }
+
+    /// Check whether the average execution time exceeds the budget of the probe.
+    /// Returns false when no budget is declared or when there is no execution.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    /// use std::time::Duration;
+    ///
+    /// exec_duration::set_budget("render", Duration::from_millis(16));
+    /// let mut ep = ExecProbe::new("render");
+    /// ep.add_point("line 1");
+    /// ep.stop();
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter().filter(|r| r.is_over_budget()) {
+    ///     println!("[{}] is over budget: {:?}", r.get_name(), r.get_avg_duration());
+    /// }
+    /// ```
+    pub fn is_over_budget(&self) -> bool {
+        match self.budget {
+            Some(budget) if self.count > 0 => self.duration / self.count as DurationUnit > budget,
+            _ => false,
+        }
+    }
+
+    /// Get the level of this node in the results tree, top-level nodes have a depth of 0
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    ///
+    /// // code
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter() {
+    ///     for part in r.get_elements().iter() {
+    ///         println!("{}[{}]", " ".repeat(part.get_depth()), part.get_name());
+    ///     }
+    /// }
+    /// ```
+    pub fn get_depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Get elements if any.
+    /// Elements are in first-insertion order: the order in which the points of a probe were first added,
+    /// across all its executions, followed by the `TAIL` element if any.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    ///
+    /// // code
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter() {
+    ///     for part in r.get_elements().iter() {
+    ///         println!("[{}::{}] costs {} seconds ({}%)",
+    ///             r.get_name(), part.get_name(),
+    ///             part.get_total_duration().as_secs(), part.get_exec_percent()
+    ///         );
+    ///     }
+    /// }
+    /// ```
+    pub fn get_elements(&self) -> &[ExecDuration] {
+        &self.childs
+    }
+
+    /// Get the custom aggregate if an aggregator was registered for this probe using `exec_duration::set_aggregator`
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::{CustomAgg, ExecProbe};
+    ///
+    /// exec_duration::set_aggregator("main", |agg: &mut CustomAgg, _| {
+    ///     *agg.get_or_default::<u64>() += 1;
+    /// });
+    /// let mut ep = ExecProbe::new("main");
+    ///
+    /// // code
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter() {
+    ///     if let Some(n) = r.get_custom().and_then(|c| c.downcast_ref::<u64>()) {
+    ///         println!("[{}] custom aggregate: {}", r.get_name(), n);
+    ///     }
+    /// }
+    /// ```
+    pub fn get_custom(&self) -> Option<&dyn Any> {
+        self.custom.as_ref().map(|c| c.as_any())
+    }
+
+    /// Serialize this node, with its elements, to JSON
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    ///
+    /// // code
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter() {
+    ///     println!("{}", r.to_json().unwrap());
+    /// }
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    /// Serialize this node, with its elements, to JSON with the durations as seconds rounded to `precision` decimals,
+    /// for consumers not expecting nanoseconds. Only the names, labels, counts and durations (`total`, `avg`,
+    /// `min` and `max`) are part of this representation, which can not be read back with `from_json`.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration::output::ExecDuration;
+    ///
+    /// let r = ExecDuration::leaf("main", 2, 1_500_000_000);
+    /// assert!(r.to_json_seconds(3).unwrap().contains("\"total\":1.5"));
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn to_json_seconds(&self, precision: u32) -> serde_json::Result<String> {
+        serde_json::to_string(&SecondsView::new(self, precision))
+    }
+
+    /// Deserialize a node, with its elements, from JSON
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    /// use exec_duration::output::ExecDuration;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    ///
+    /// // code
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter() {
+    ///     let json = r.to_json().unwrap();
+    ///     assert_eq!(&ExecDuration::from_json(&json).unwrap(), r);
+    /// }
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &str) -> serde_json::Result<ExecDuration> {
+        serde_json::from_str(json)
+    }
+
+    /// Get measured code block name
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::ExecProbe;
+    ///
+    /// let mut ep = ExecProbe::new("main");
+    ///
+    /// // code
+    ///
+    /// // fetch results
+    /// let list = exec_duration::fetch_results();
+    /// for r in list.iter() {
+    ///     println!("[{}] costs {} seconds (~{} seconds in average)",
+    ///         r.get_name(), r.get_total_duration().as_secs(),
+    ///         r.get_avg_duration().as_secs()
+    ///     );
+    /// }
+    /// ```
+    pub fn get_name(&self) -> &str {
+        self.name.as_str()
+    }
+}
+
+/// Top-line numbers of execution metrics, as returned by `ExecDuration::summary`.
+/// Unlike `ExecDuration`, it holds no elements, which keeps it small once serialized.
+///
+/// # Examples
+/// ```
+/// use exec_duration::output::ExecDuration;
+/// use std::time::Duration;
+///
+/// let r = ExecDuration::builder("main").count(2).total(Duration::from_nanos(300)).build().unwrap();
+/// let s = r.summary();
+/// assert_eq!(s.count, 2);
+/// assert_eq!(s.avg, 150);
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub struct Summary {
+    /// Name of the probe
+    pub name: String,
+    /// Number of executions
+    pub count: u64,
+    /// Total execution time, in nanoseconds
+    pub total: DurationUnit,
+    /// Average execution time, in nanoseconds
+    pub avg: DurationUnit,
+}
+
+/// Execution metrics builder.
+/// Instances are created using `ExecDuration::builder` function.
+///
+/// # Examples
+/// ```
+/// use exec_duration::output::ExecDuration;
+/// use std::time::Duration;
+///
+/// let r = ExecDuration::builder("main")
+///     .count(1)
+///     .total(Duration::from_millis(10))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ExecDurationBuilder {
+    name: String,
+    count: u64,
+    total: Duration,
+    childs: Vec<ExecDuration>,
+}
+
+impl ExecDurationBuilder {
+    /// Set the execution count, it must be greater than zero
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration::output::ExecDuration;
+    ///
+    /// let r = ExecDuration::builder("main").count(3).build().unwrap();
+    /// assert_eq!(r.get_exec_count(), 3);
+    /// ```
+    pub fn count(mut self, count: u64) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// Set the total execution time
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration::output::ExecDuration;
+    /// use std::time::Duration;
+    ///
+    /// let r = ExecDuration::builder("main")
+    ///     .count(1)
+    ///     .total(Duration::from_millis(10))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(r.get_total_duration(), Duration::from_millis(10));
+    /// ```
+    pub fn total(mut self, total: Duration) -> Self {
+        self.total = total;
+        self
+    }
+
+    /// Add an element, its percentage is computed against the total execution time of the built node
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration::output::ExecDuration;
+    /// use std::time::Duration;
+    ///
+    /// let part = ExecDuration::builder("part 1")
+    ///     .count(1)
+    ///     .total(Duration::from_millis(5))
+    ///     .build()
+    ///     .unwrap();
+    /// let r = ExecDuration::builder("main")
+    ///     .count(1)
+    ///     .total(Duration::from_millis(10))
+    ///     .child(part)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(r.get_elements()[0].get_exec_percent(), 50);
+    /// ```
+    pub fn child(mut self, child: ExecDuration) -> Self {
+        self.childs.push(child);
+        self
+    }
+
+    /// Build the execution metrics
+    ///
+    /// # Errors
+    ///
+    /// `ValidationError::ZeroCount` if the execution count is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration::output::ExecDuration;
+    ///
+    /// assert!(ExecDuration::builder("main").count(1).build().is_ok());
+    /// assert!(ExecDuration::builder("main").build().is_err());
+    /// ```
+    pub fn build(self) -> Result<ExecDuration, ValidationError> {
+        if self.count == 0 {
+            return Err(ValidationError::ZeroCount(self.name));
+        }
+        let duration = self.total.as_nanos();
+        let mut elt = ExecDuration::new(&self.name, self.count, duration, duration);
+        for mut child in self.childs.into_iter() {
+            child.set_total(duration);
+            elt.add(child);
+        }
+        Ok(elt)
+    }
+}
+
+/// Key used to rank execution metrics
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::output::SortKey;
+///
+/// let list = exec_duration::fetch_top(5, SortKey::AvgDuration);
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum SortKey {
+    /// Total execution time
+    TotalDuration,
+    /// Average execution time
+    AvgDuration,
+    /// Execution count
+    ExecCount,
+}
+
+impl SortKey {
+    pub(crate) fn key(&self, count: u64, duration: DurationUnit) -> DurationUnit {
+        match self {
+            SortKey::TotalDuration => duration,
+            SortKey::AvgDuration if count == 0 => 0,
+            SortKey::AvgDuration => duration / count as DurationUnit,
+            SortKey::ExecCount => count as DurationUnit,
+        }
+    }
+}
+
+/// Execution metrics merged by name
+///
+/// Collecting `ExecDuration` entries into `Results` merges the entries sharing the same name and labels:
+/// counts and durations are summed and children are merged recursively.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ExecProbe;
+/// use exec_duration::output::Results;
+///
+/// let mut ep = ExecProbe::new("main");
+///
+/// // code
+///
+/// let results: Results = exec_duration::fetch_results().into_iter().collect();
+/// for r in results.iter() {
+///     println!("{}", r);
+/// }
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Results(Vec<ExecDuration>);
+
+impl Results {
+    /// Get the merged entries
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration;
+    /// use exec_duration::output::Results;
+    ///
+    /// let results: Results = exec_duration::fetch_results().into_iter().collect();
+    /// let list = results.into_vec();
+    /// ```
+    pub fn into_vec(self) -> Vec<ExecDuration> {
+        self.0
+    }
+}
+
+impl FromIterator<ExecDuration> for Results {
+    fn from_iter<I: IntoIterator<Item = ExecDuration>>(iter: I) -> Self {
+        let mut list: Vec<ExecDuration> = Vec::new();
+        for v in iter {
+            match list
+                .iter_mut()
+                .find(|e| e.name == v.name && e.labels == v.labels)
+            {
+                Some(e) => e.merge(v),
+                None => list.push(v),
+            }
+        }
+        Results(list)
+    }
+}
+
+impl Deref for Results {
+    type Target = [ExecDuration];
+
+    fn deref(&self) -> &[ExecDuration] {
+        &self.0
+    }
+}
+
+impl IntoIterator for Results {
+    type Item = ExecDuration;
+    type IntoIter = std::vec::IntoIter<ExecDuration>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl From<Results> for Vec<ExecDuration> {
+    fn from(results: Results) -> Self {
+        results.0
+    }
+}
+
+/// Serialized form of `ExecDuration::to_json_seconds`
+#[cfg(feature = "json")]
+#[derive(Serialize)]
+struct SecondsView<'a> {
+    name: &'a str,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    labels: &'a BTreeMap<String, String>,
+    count: u64,
+    total: f64,
+    avg: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max: Option<f64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    childs: Vec<SecondsView<'a>>,
+}
+
+#[cfg(feature = "json")]
+impl<'a> SecondsView<'a> {
+    fn new(r: &'a ExecDuration, precision: u32) -> Self {
+        let factor = 10f64.powi(precision.min(9) as i32);
+        let seconds = |d: Duration| (d.as_secs_f64() * factor).round() / factor;
+        SecondsView {
+            name: &r.name,
+            labels: &r.labels,
+            count: r.count,
+            total: seconds(r.get_total_duration()),
+            avg: seconds(r.get_avg_duration()),
+            min: r.get_min_duration().map(seconds),
+            max: r.get_max_duration().map(seconds),
+            childs: r
+                .childs
+                .iter()
+                .map(|c| SecondsView::new(c, precision))
+                .collect(),
+        }
+    }
+}
+
+/// Write execution metrics as line-delimited JSON, one top-level node per line
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::output;
+///
+/// let list = exec_duration::fetch_results();
+/// let mut out = Vec::new();
+/// output::to_ndjson(&list, &mut out).unwrap();
+/// ```
+#[cfg(feature = "json")]
+pub fn to_ndjson<W: std::io::Write>(results: &[ExecDuration], w: &mut W) -> serde_json::Result<()> {
+    for r in results.iter() {
+        serde_json::to_writer(&mut *w, r)?;
+        w.write_all(b"\n").map_err(serde_json::Error::io)?;
+    }
+    Ok(())
+}
+
+/// Read execution metrics from line-delimited JSON, merging the nodes sharing the same name as `Results` does.
+/// Blank lines are ignored.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::output;
+///
+/// let list = exec_duration::fetch_results();
+/// let mut out = Vec::new();
+/// output::to_ndjson(&list, &mut out).unwrap();
+/// assert_eq!(output::from_ndjson(&mut out.as_slice()).unwrap(), list);
+/// ```
+#[cfg(feature = "json")]
+pub fn from_ndjson<R: std::io::Read>(r: &mut R) -> serde_json::Result<Vec<ExecDuration>> {
+    use std::io::BufRead;
+
+    let mut list: Vec<ExecDuration> = Vec::new();
+    for line in std::io::BufReader::new(r).lines() {
+        let line = line.map_err(serde_json::Error::io)?;
+        if !line.trim().is_empty() {
+            list.push(serde_json::from_str(&line)?);
+        }
+    }
+    Ok(list.into_iter().collect::<Results>().into_vec())
+}
+
+/// Replace the name of each node with a SipHash of the name, seeded with `salt`, preserving the tree structure
+/// and the numbers. Without the salt, the names can not be recovered by hashing candidate names, so keep it secret.
+/// The hash is stable: a name always maps to the same hash for a given salt and Rust toolchain, so anonymized
+/// reports remain comparable. Only the names are replaced, labels and counters are kept as is,
+/// as well as the `TAIL` elements.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::output;
+///
+/// let list = exec_duration::fetch_results();
+/// for r in output::anonymize(&list, "secret").iter() {
+///     println!("{}", r);
+/// }
+/// ```
+pub fn anonymize(results: &[ExecDuration], salt: &str) -> Vec<ExecDuration> {
+    use std::collections::hash_map::DefaultHasher;
+
+    fn rename(r: &mut ExecDuration, seeded: &DefaultHasher) {
+        if r.name != TAIL {
+            let mut hasher = seeded.clone();
+            r.name.hash(&mut hasher);
+            r.name = format!("{:016x}", hasher.finish());
+        }
+        for child in r.childs.iter_mut() {
+            rename(child, seeded);
+        }
+    }
+
+    // the state after hashing the salt is the key of the hashes of the names
+    let mut seeded = DefaultHasher::new();
+    salt.hash(&mut seeded);
+    let mut list = results.to_vec();
+    for r in list.iter_mut() {
+        rename(r, &seeded);
+    }
+    list
+}
+
+/// Write a summary of execution metrics, one row per probe followed by one row per element.
+/// When `color` is set, rows are colored by percentage: red from 50%, yellow from 20%, green below.
+/// The percentage of a probe is its share of the total duration of all probes, the one of an element is
+/// its share of the probe duration.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::output;
+///
+/// let list = exec_duration::fetch_results();
+/// let mut out = Vec::new();
+/// output::write_summary_colored(&mut out, &list, false).unwrap();
+/// ```
+#[cfg(feature = "color")]
+pub fn write_summary_colored<W: std::io::Write>(
+    w: &mut W,
+    results: &[ExecDuration],
+    color: bool,
+) -> std::io::Result<()> {
+    use owo_colors::OwoColorize;
+
+    fn row<W: std::io::Write>(
+        w: &mut W,
+        r: &ExecDuration,
+        percent: f64,
+        color: bool,
+    ) -> std::io::Result<()> {
+        let text = format!(
+            "{}[{}] {:.1}% Call: {:?} T: {:?} Avg: {:?}",
+            "  ".repeat(r.depth),
+            r.name,
+            percent,
+            r.count,
+            r.get_total_duration(),
+            r.get_avg_duration(),
+        );
+        if !color {
+            writeln!(w, "{}", text)
+        } else if percent >= 50.0 {
+            writeln!(w, "{}", text.red())
+        } else if percent >= 20.0 {
+            writeln!(w, "{}", text.yellow())
+        } else {
+            writeln!(w, "{}", text.green())
+        }
+    }
+
+    let total: DurationUnit = results.iter().map(|r| r.duration).sum();
+    for r in results.iter() {
+        let percent = if total == 0 {
+            0.0
+        } else {
+            r.duration as f64 * 100.0 / total as f64
+        };
+        row(w, r, percent, color)?;
+        for child in r.childs.iter() {
+            row(w, child, child.get_exec_percent_f64(), color)?;
+        }
+    }
+    Ok(())
+}
+
+/// Comparison of a probe between two reports, as returned by `diff`.
+/// Changes are relative, in percent of the baseline value.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::output;
+///
+/// let baseline = exec_duration::fetch_results();
+/// let current = exec_duration::fetch_results();
+/// for d in output::diff(&baseline, &current).iter() {
+///     println!("[{}] {:+.1}%", d.name, d.avg_change);
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct ProbeDiff {
+    /// Name of the probe
+    pub name: String,
+    /// Labels of the probe
+    pub labels: BTreeMap<String, String>,
+    /// Average execution time in the baseline report
+    pub avg_baseline: Duration,
+    /// Average execution time in the current report
+    pub avg_current: Duration,
+    /// Change of the average execution time
+    pub avg_change: f64,
+    /// 95th percentile of the execution time in the baseline report, if percentiles are estimated
+    pub p95_baseline: Option<Duration>,
+    /// 95th percentile of the execution time in the current report, if percentiles are estimated
+    pub p95_current: Option<Duration>,
+    /// Change of the 95th percentile, if percentiles are estimated in both reports
+    pub p95_change: Option<f64>,
+}
+
+/// Compare two reports, probe by probe.
+/// Only the probes present in both reports are compared, they are matched by name and labels.
+/// The 95th percentiles are compared when they are estimated in both reports, which requires the `tdigest` feature.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::output;
+///
+/// let baseline = exec_duration::fetch_results();
+/// // code
+/// let current = exec_duration::fetch_results();
+/// for d in output::diff(&baseline, &current).iter().filter(|d| d.avg_change > 10.0) {
+///     println!("[{}] regressed from {:?} to {:?}", d.name, d.avg_baseline, d.avg_current);
+/// }
+/// ```
+pub fn diff(baseline: &[ExecDuration], current: &[ExecDuration]) -> Vec<ProbeDiff> {
+    fn change(baseline: Duration, current: Duration) -> f64 {
+        let (baseline, current) = (baseline.as_nanos() as f64, current.as_nanos() as f64);
+        if baseline == 0.0 {
+            return if current == 0.0 { 0.0 } else { f64::INFINITY };
+        }
+        (current - baseline) * 100.0 / baseline
+    }
+
+    #[cfg(feature = "tdigest")]
+    fn p95(r: &ExecDuration) -> Option<Duration> {
+        r.get_percentile(95.0)
+    }
+
+    #[cfg(not(feature = "tdigest"))]
+    fn p95(_: &ExecDuration) -> Option<Duration> {
+        None
+    }
+
+    current
+        .iter()
+        .filter_map(|c| {
+            let b = baseline
+                .iter()
+                .find(|b| b.name == c.name && b.labels == c.labels)?;
+            let (p95_baseline, p95_current) = (p95(b), p95(c));
+            Some(ProbeDiff {
+                name: c.name.to_string(),
+                labels: c.labels.clone(),
+                avg_baseline: b.get_avg_duration(),
+                avg_current: c.get_avg_duration(),
+                avg_change: change(b.get_avg_duration(), c.get_avg_duration()),
+                p95_baseline,
+                p95_current,
+                p95_change: match (p95_baseline, p95_current) {
+                    (Some(b), Some(c)) => Some(change(b, c)),
+                    _ => None,
+                },
+            })
+        })
+        .collect()
+}
+
+/// Maximum number of elements shown by `Display`, see `set_max_display_children`
+static MAX_DISPLAY_CHILDREN: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+/// Limit the number of elements shown by the `Display` implementation of `ExecDuration` (unlimited by default).
+/// Beyond the limit, only the `n` longest elements are shown, in their original order,
+/// followed by a `(+M more)` line accounting for the hidden ones.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::output;
+///
+/// output::set_max_display_children(10);
+/// for r in exec_duration::fetch_results().iter() {
+///     println!("{}", r);
+/// }
+/// ```
+pub fn set_max_display_children(n: usize) {
+    MAX_DISPLAY_CHILDREN.store(n, Ordering::Relaxed);
+}
+
+static NAME_SEPARATOR: RwLock<Option<String>> = RwLock::new(None);
+
+/// Set the separator joining the name of an element to the names of its ancestors, as in `main::part 1`.
+/// By default, the `Display` implementation of `ExecDuration` prefixes the first line of an element with the name
+/// of its parent in brackets (`[main] [part 1]`); once a separator is set, every element is shown by its full path.
+/// The separator also splits the probe names in `exec_duration::fetch_grouped_by_name_separator`.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::output;
+///
+/// output::set_name_separator("/");
+/// for r in exec_duration::fetch_results().iter() {
+///     print!("{}", r);
+/// }
+/// ```
+pub fn set_name_separator(separator: &str) {
+    let mut current = NAME_SEPARATOR.write().unwrap_or_else(|e| e.into_inner());
+    *current = Some(separator.to_string());
+}
+
+pub(crate) fn name_separator() -> Option<String> {
+    let current = NAME_SEPARATOR.read().unwrap_or_else(|e| e.into_inner());
+    current.clone()
+}
+
+/// Get the harmonic mean of the throughputs of the given probes, the correct average of rates.
+/// Probes without execution are ignored, probes measured as zero nanoseconds have an infinite throughput and
+/// do not lower the mean. Returns 0 when no probe has executions.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::output;
+///
+/// let list = exec_duration::fetch_results();
+/// println!("{:.1} ops/s", output::harmonic_mean_throughput(&list));
+/// ```
+pub fn harmonic_mean_throughput(results: &[ExecDuration]) -> f64 {
+    let rates: Vec<f64> = results
+        .iter()
+        .filter(|r| r.count > 0)
+        .map(|r| r.throughput())
+        .collect();
+    if rates.is_empty() {
+        return 0.0;
+    }
+    let reciprocals: f64 = rates.iter().map(|t| 1.0 / t).sum();
+    rates.len() as f64 / reciprocals
+}
+
+/// Maximum width of the name column of `table`, longer names are truncated with an ellipsis
+pub const TABLE_NAME_WIDTH: usize = 40;
+
+/// Render a fixed-width table of execution metrics, one row per probe followed by one row per element.
+/// Column widths are computed from the data, numbers are right-aligned and names longer than
+/// `TABLE_NAME_WIDTH` are truncated with an ellipsis.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::output;
+///
+/// let list = exec_duration::fetch_results();
+/// print!("{}", output::table(&list));
+/// ```
+pub fn table(results: &[ExecDuration]) -> String {
+    fn name(r: &ExecDuration) -> String {
+        let name = format!("{}{}", "  ".repeat(r.depth), r.name);
+        if name.chars().count() <= TABLE_NAME_WIDTH {
+            return name;
+        }
+        let mut truncated: String = name.chars().take(TABLE_NAME_WIDTH - 1).collect();
+        truncated.push('…');
+        truncated
+    }
+
+    let header = ["Name", "%", "Calls", "Total", "Avg"];
+    let mut rows = Vec::new();
+    for r in results
+        .iter()
+        .flat_map(|r| std::iter::once(r).chain(r.childs.iter()))
+    {
+        rows.push([
+            name(r),
+            format!("{:.1}", r.get_exec_percent_f64()),
+            r.count.to_string(),
+            format!("{:?}", r.get_total_duration()),
+            format!("{:?}", r.get_avg_duration()),
+        ]);
+    }
+    let mut widths = header.map(|h| h.chars().count());
+    for row in rows.iter() {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut out = String::new();
+    let header = header.map(|h| h.to_string());
+    for row in std::iter::once(&header).chain(rows.iter()) {
+        let mut line = format!("{:<w$}", row[0], w = widths[0]);
+        for (cell, width) in row.iter().zip(widths.iter()).skip(1) {
+            line.push_str(&format!("  {:>w$}", cell, w = width));
+        }
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+/// Convert execution metrics to an Arrow record batch, one row per probe and per element (depth-first),
+/// to feed analytics pipelines such as DataFusion or Polars.
+/// Columns are `name`, `parent` (null for probes), `count`, `total_nanos`, `avg_nanos` and `percent`.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::output;
+///
+/// let list = exec_duration::fetch_results();
+/// let batch = output::to_arrow(&list);
+/// println!("{} rows", batch.num_rows());
+/// ```
+#[cfg(feature = "arrow")]
+pub fn to_arrow(results: &[ExecDuration]) -> arrow_array::RecordBatch {
+    use arrow_array::{ArrayRef, Float64Array, RecordBatch, StringArray, UInt64Array};
+    use arrow_schema::{DataType, Field, Schema};
+    use std::sync::Arc;
+
+    #[derive(Default)]
+    struct Columns<'a> {
+        name: Vec<&'a str>,
+        parent: Vec<Option<&'a str>>,
+        count: Vec<u64>,
+        total: Vec<u64>,
+        avg: Vec<u64>,
+        percent: Vec<f64>,
+    }
+
+    fn flatten<'a>(c: &mut Columns<'a>, r: &'a ExecDuration, parent: Option<&'a str>) {
+        c.name.push(&r.name);
+        c.parent.push(parent);
+        c.count.push(r.count);
+        c.total.push(r.get_total_duration().as_nanos() as u64);
+        c.avg.push(r.get_avg_duration().as_nanos() as u64);
+        c.percent.push(r.get_exec_percent_f64());
+        for child in r.childs.iter() {
+            flatten(c, child, Some(&r.name));
+        }
+    }
+
+    let mut c = Columns::default();
+    for r in results.iter() {
+        flatten(&mut c, r, None);
+    }
+    let schema = Schema::new(vec![
+        Field::new("name", DataType::Utf8, false),
+        Field::new("parent", DataType::Utf8, true),
+        Field::new("count", DataType::UInt64, false),
+        Field::new("total_nanos", DataType::UInt64, false),
+        Field::new("avg_nanos", DataType::UInt64, false),
+        Field::new("percent", DataType::Float64, false),
+    ]);
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from(c.name)),
+        Arc::new(StringArray::from(c.parent)),
+        Arc::new(UInt64Array::from(c.count)),
+        Arc::new(UInt64Array::from(c.total)),
+        Arc::new(UInt64Array::from(c.avg)),
+        Arc::new(Float64Array::from(c.percent)),
+    ];
+    RecordBatch::try_new(Arc::new(schema), columns).expect("columns match the schema")
+}
+
+/// Render the probe tree as a Graphviz `digraph`.
+/// Each probe and element is a node labeled with its total duration and percentage, edges connect
+/// a probe to its elements. Nodes are filled from green to red as their percentage grows.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::output;
+///
+/// let list = exec_duration::fetch_results();
+/// std::fs::write(std::env::temp_dir().join("probes.dot"), output::to_dot(&list)).unwrap();
+/// ```
+pub fn to_dot(results: &[ExecDuration]) -> String {
+    use std::fmt::Write;
+
+    fn node(out: &mut String, r: &ExecDuration, next: &mut usize) -> usize {
+        let id = *next;
+        *next += 1;
+        let percent = r.get_exec_percent_f64();
+        // hue from green (1/3) down to red (0)
+        let hue = (1.0 - percent.clamp(0.0, 100.0) / 100.0) / 3.0;
+        let name = r.name.replace('\\', "\\\\").replace('"', "\\\"");
+        let _ = writeln!(
+            out,
+            "    n{} [label=\"{}\\n{:?} ({:.1}%)\", fillcolor=\"{:.3} 0.600 0.950\"];",
+            id,
+            name,
+            r.get_total_duration(),
+            percent,
+            hue,
+        );
+        for child in r.childs.iter() {
+            let child_id = node(out, child, next);
+            let _ = writeln!(out, "    n{} -> n{};", id, child_id);
+        }
+        id
+    }
+
+    let mut out = String::from("digraph probes {\n    node [shape=box, style=filled];\n");
+    let mut next = 0;
+    for r in results.iter() {
+        node(&mut out, r, &mut next);
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Group results under synthetic parents, one per key, keeping the order of first appearance.
+/// The parents sum the counts and durations of their children.
+pub(crate) fn group_by<F>(results: Vec<ExecDuration>, key: F) -> Vec<ExecDuration>
+where
+    F: Fn(&ExecDuration) -> String,
+{
+    let mut groups: Vec<ExecDuration> = Vec::new();
+    for r in results.into_iter() {
+        let k = key(&r);
+        let pos = match groups.iter().position(|g| g.name == k) {
+            Some(pos) => pos,
+            None => {
+                groups.push(ExecDuration::new(&k, 0, 0, 0));
+                groups.len() - 1
+            }
+        };
+        let group = &mut groups[pos];
+        group.count += r.count;
+        group.duration += r.duration;
+        group.total = group.duration;
+        group.add(r);
+    }
+    for group in groups.iter_mut() {
+        let total = group.duration;
+        for child in group.childs.iter_mut() {
+            child.set_total(total);
+        }
+    }
+
+    groups
+}
+
+impl ExecDuration {
+    /// Get the elements shown by `Display`, the longest ones when there are more than `set_max_display_children`,
+    /// in their original order, and the number of elements left out
+    fn shown_childs(&self) -> (Vec<&ExecDuration>, usize) {
+        let max = MAX_DISPLAY_CHILDREN.load(Ordering::Relaxed);
+        if self.childs.len() <= max {
+            return (self.childs.iter().collect(), 0);
+        }
+        let mut longest: Vec<usize> = (0..self.childs.len()).collect();
+        longest.sort_by_key(|i| std::cmp::Reverse(self.childs[*i].duration));
+        longest.truncate(max);
+        longest.sort_unstable();
+        let shown = longest.iter().map(|i| &self.childs[*i]).collect();
+        (shown, self.childs.len() - max)
+    }
+
+    /// Write these metrics and their elements, the first line named by `label`.
+    /// With a separator, each line is named by the full path of its node, see `set_name_separator`.
+    /// Without, the first line of an element is prefixed with the name of its parent in brackets.
+    fn fmt_tree(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        label: &str,
+        separator: Option<&str>,
+    ) -> fmt::Result {
+        writeln!(
+            f,
+            "[{}] {}% Call: {:?} T: {:?} Avg: {:?}",
+            label,
+            self.get_exec_percent(),
+            self.get_exec_count(),
+            self.get_total_duration(),
+            self.get_avg_duration(),
+        )?;
+        let (shown, hidden) = self.shown_childs();
+        for child in shown {
+            let child_label = match separator {
+                Some(separator) => format!("{}{}{}", label, separator, child.name),
+                // written as `[parent] [child]` by the surrounding brackets
+                None => format!("{}] [{}", self.name, child.name),
+            };
+            child.fmt_tree(f, &child_label, separator)?;
+        }
+        if hidden > 0 {
+            let label = separator.map_or(self.name.as_str(), |_| label);
+            writeln!(f, "[{}] (+{} more)", label, hidden)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ExecDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_tree(f, &self.name, name_separator().as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::output::{ExecDuration, Results};
+    use std::time::Duration;
+
+    #[test]
+    fn exec_percent_f64() {
+        let mut r = ExecDuration::new("main", 1, 1000, 1000);
+        r.add(ExecDuration::new("small", 1, 3, 1000));
+        r.add(ExecDuration::new("large", 1, 997, 1000));
+
+        let small = &r.get_elements()[0];
+        assert_eq!(small.get_exec_percent(), 0);
+        assert!((small.get_exec_percent_f64() - 0.3).abs() < 1e-9);
+        assert!((r.get_exec_percent_f64() - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn collect_merges_by_name() {
+        let mut a = ExecDuration::new("main", 2, 300, 300);
+        a.add(ExecDuration::new("part 1", 2, 100, 300));
+        let mut b = ExecDuration::new("main", 1, 200, 200);
+        b.add(ExecDuration::new("part 1", 1, 50, 200));
+        b.add(ExecDuration::new("part 2", 1, 150, 200));
+        let c = ExecDuration::new("other", 1, 10, 10);
+
+        let results: Results = vec![a, c, b].into_iter().collect();
+        assert_eq!(results.len(), 2);
+        let main = &results[0];
+        assert_eq!(main.get_name(), "main");
+        assert_eq!(main.get_exec_count(), 3);
+        assert_eq!(main.get_total_duration().as_nanos(), 500);
+        assert_eq!(main.get_exec_percent(), 100);
+        let parts = main.get_elements();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].get_name(), "part 1");
+        assert_eq!(parts[0].get_exec_count(), 3);
+        assert_eq!(parts[0].get_total_duration().as_nanos(), 150);
+        assert_eq!(parts[0].get_exec_percent(), 30);
+        assert_eq!(parts[1].get_name(), "part 2");
+        assert_eq!(parts[1].get_exec_percent(), 30);
+        assert_eq!(results[1].get_name(), "other");
+    }
+
+    #[test]
+    fn self_duration() {
+        let mut group = ExecDuration::new("db", 2, 1000, 1000);
+        group.add(ExecDuration::new("db.a", 1, 300, 1000));
+        group.add(ExecDuration::new("db.b", 1, 450, 1000));
+        assert_eq!(group.get_self_duration().as_nanos(), 250);
+        assert_eq!(group.get_elements()[0].get_self_duration().as_nanos(), 300);
+
+        group.add(ExecDuration::new("db.c", 1, 500, 1000));
+        assert_eq!(group.get_self_duration().as_nanos(), 0);
+    }
+
+    #[test]
+    fn anonymize() {
+        let mut main = ExecDuration::new("main", 2, 1000, 1000);
+        main.add(ExecDuration::new("part 1", 2, 400, 1000));
+        main.add(ExecDuration::new("part 2", 2, 500, 1000));
+        main.add(ExecDuration::new(super::TAIL, 2, 100, 1000));
+        let other = ExecDuration::new("main", 1, 10, 10);
+
+        let list = crate::output::anonymize(&[main.clone(), other], "salt");
+        assert_eq!(list.len(), 2);
+        let r = &list[0];
+        assert_ne!(r.get_name(), "main");
+        assert_eq!(r.get_name(), list[1].get_name());
+        assert_eq!(r.get_exec_count(), 2);
+        assert_eq!(r.get_total_duration(), main.get_total_duration());
+        let parts = r.get_elements();
+        assert_eq!(parts.len(), 3);
+        assert_ne!(parts[0].get_name(), parts[1].get_name());
+        assert_eq!(parts[0].get_total_duration().as_nanos(), 400);
+        assert_eq!(parts[1].get_exec_percent(), 50);
+        assert_eq!(parts[1].get_depth(), 1);
+        assert_eq!(parts[2].get_name(), super::TAIL);
+
+        let again = crate::output::anonymize(&[main.clone()], "salt");
+        assert_eq!(again[0].get_name(), r.get_name());
+        let other_salt = crate::output::anonymize(&[main], "pepper");
+        assert_ne!(other_salt[0].get_name(), r.get_name());
+    }
+
+    #[test]
+    fn diff() {
+        let baseline = [ExecDuration::new("main", 2, 2000, 2000)];
+        let current = [
+            ExecDuration::new("main", 2, 3000, 3000),
+            ExecDuration::new("new", 1, 10, 10),
+        ];
+        let list = crate::output::diff(&baseline, &current);
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].name, "main");
+        assert_eq!(list[0].avg_baseline.as_nanos(), 1000);
+        assert_eq!(list[0].avg_current.as_nanos(), 1500);
+        assert!((list[0].avg_change - 50.0).abs() < 1e-9);
+        assert_eq!(list[0].p95_change, None);
+    }
+
+    #[test]
+    fn avg_percent() {
+        // every execution goes through both points, the second one twice
+        let mut r = ExecDuration::new("main", 4, 4000, 4000);
+        r.add(ExecDuration::new("part 1", 4, 1000, 4000));
+        r.add(ExecDuration::new("part 2", 8, 3000, 4000));
+        // reached once, by an execution much longer than the others
+        r.add(ExecDuration::new("rare", 1, 3000, 4000));
+
+        let elements = r.get_elements();
+        assert_eq!(elements[0].get_avg_percent(), 25);
+        assert_eq!(elements[1].get_avg_percent(), 37);
+        assert_eq!(elements[2].get_avg_percent(), 255);
+        assert_eq!(r.get_avg_percent(), 100);
+        assert_eq!(ExecDuration::new("zero", 1, 0, 0).get_avg_percent(), 0);
+
+        let mut r = ExecDuration::new("main", 3, 3000, 3000);
+        for (name, d) in [("a", 1000), ("b", 1500), ("c", 500)] {
+            r.add(ExecDuration::new(name, 3, d, 3000));
+        }
+        let sum: u32 = r
+            .get_elements()
+            .iter()
+            .map(|e| e.get_avg_percent() as u32)
+            .sum();
+        assert!((99..=100).contains(&sum), "{}", sum);
+    }
+
+    #[test]
+    fn percent_of() {
+        let build = |name: &str, ms: u64, childs: Vec<ExecDuration>| {
+            let builder = ExecDuration::builder(name)
+                .count(1)
+                .total(Duration::from_millis(ms));
+            childs
+                .into_iter()
+                .fold(builder, |b, c| b.child(c))
+                .build()
+                .unwrap()
+        };
+        let grandchild = build("query", 30, Vec::new());
+        let child = build("db", 60, vec![grandchild]);
+        let main = build("main", 200, vec![child]);
+
+        let grandchild = &main.get_elements()[0].get_elements()[0];
+        assert!((grandchild.get_exec_percent_f64() - 50.0).abs() < 1e-9);
+        assert!((grandchild.percent_of(main.get_total_duration()) - 15.0).abs() < 1e-9);
+        assert_eq!(grandchild.percent_of(Duration::ZERO), 0.0);
+    }
+
+    #[test]
+    fn validate() {
+        use crate::ValidationError;
+
+        let mut r = ExecDuration::new("main", 2, 1000, 1000);
+        r.add(ExecDuration::new("part 1", 2, 400, 1000));
+        r.add(ExecDuration::new("part 2", 2, 700, 1000));
+        // elements may overlap
+        assert_eq!(r.validate(), Ok(()));
+
+        let mut bad = r.clone();
+        bad.count = 0;
+        assert_eq!(
+            bad.validate(),
+            Err(ValidationError::ZeroCount("main".to_string()))
+        );
+
+        let mut bad = r.clone();
+        bad.childs[1].duration = 1500;
+        let err = bad.validate().unwrap_err();
+        assert_eq!(
+            err,
+            ValidationError::ChildExceedsParent("part 2".to_string())
+        );
+        assert_eq!(err.to_string(), "[part 2] lasts longer than its parent");
+
+        let mut bad = r.clone();
+        bad.childs[0].total = 2000;
+        assert_eq!(
+            bad.validate(),
+            Err(ValidationError::TotalMismatch("part 1".to_string()))
+        );
+
+        let mut bad = r;
+        bad.childs[0].zero_count = 3;
+        assert_eq!(
+            bad.validate(),
+            Err(ValidationError::CountMismatch("part 1".to_string()))
+        );
+    }
+
+    #[test]
+    fn synthetic_samples() {
+        let mut r = ExecDuration::new("main", 5, 1000, 1000);
+        r.set_min_max(Some(120), Some(260));
+        let samples = r.synthetic_samples();
+        assert_eq!(samples.len() as u64, r.get_exec_count());
+        let sum: Duration = samples.iter().sum();
+        assert_eq!(sum / samples.len() as u32, r.get_avg_duration());
+        // as wide as the longest execution allows
+        assert_eq!(samples[0], Duration::from_nanos(140));
+        assert_eq!(samples[4], Duration::from_nanos(260));
+
+        let r = ExecDuration::new("main", 4, 1000, 1000);
+        assert_eq!(r.synthetic_samples(), vec![Duration::from_nanos(250); 4]);
+        assert!(ExecDuration::default().synthetic_samples().is_empty());
+    }
+
+    #[test]
+    fn table() {
+        let mut r = ExecDuration::new("main", 2, 1000, 1000);
+        r.add(ExecDuration::new("a", 2, 250, 1000));
+        r.add(ExecDuration::new("a much longer name", 10, 750, 1000));
+        r.add(ExecDuration::new(&"x".repeat(60), 1, 0, 1000));
+
+        let table = crate::output::table(&[r]);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 5);
+        let width = lines[0].chars().count();
+        assert!(lines.iter().all(|l| l.chars().count() == width));
+        // numbers are right-aligned on the header
+        assert!(lines[0].ends_with("  Avg"));
+        assert!(lines[1].ends_with("500ns"));
+        assert!(lines[2].ends_with("125ns"));
+        let calls_end = lines[0].find("Calls").unwrap() + "Calls".len();
+        assert_eq!(&lines[1][calls_end - 1..calls_end], "2");
+        assert_eq!(&lines[3][calls_end - 2..calls_end], "10");
+        assert!(lines[2].starts_with("  a   "));
+        let truncated = format!("  {}…", "x".repeat(crate::output::TABLE_NAME_WIDTH - 3));
+        assert!(lines[4].starts_with(&truncated));
+    }
+
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn to_arrow() {
+        use arrow_array::{Array, StringArray, UInt64Array};
+
+        let mut r = ExecDuration::new("main", 2, 1000, 1000);
+        r.add(ExecDuration::new("part 1", 2, 400, 1000));
+        r.add(ExecDuration::new("part 2", 1, 600, 1000));
+        let other = ExecDuration::new("other", 1, 10, 10);
+
+        let batch = crate::output::to_arrow(&[r, other]);
+        let schema = batch.schema();
+        let columns: Vec<&str> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+        assert_eq!(
+            columns,
+            vec![
+                "name",
+                "parent",
+                "count",
+                "total_nanos",
+                "avg_nanos",
+                "percent"
+            ]
+        );
+        assert_eq!(batch.num_rows(), 4);
+        let downcast = |i: usize| {
+            batch
+                .column(i)
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .unwrap()
+        };
+        let (names, parents) = (downcast(0), downcast(1));
+        assert_eq!(names.value(1), "part 1");
+        assert_eq!(parents.value(1), "main");
+        assert!(parents.is_null(0));
+        assert!(parents.is_null(3));
+        let avg = batch
+            .column(4)
+            .as_any()
+            .downcast_ref::<UInt64Array>()
+            .unwrap();
+        assert_eq!(avg.value(0), 500);
+        assert_eq!(avg.value(2), 600);
+    }
+
+    #[test]
+    fn to_dot() {
+        let mut r = ExecDuration::new("main", 1, 1000, 1000);
+        r.add(ExecDuration::new("part 1", 1, 250, 1000));
+        r.add(ExecDuration::new("part \"2\"", 1, 750, 1000));
+
+        let dot = crate::output::to_dot(&[r]);
+        assert!(dot.starts_with("digraph probes {\n"));
+        assert!(dot.contains("n0 [label=\"main\\n1µs (100.0%)\""));
+        assert!(dot.contains("n1 [label=\"part 1\\n250ns (25.0%)\""));
+        assert!(dot.contains("n2 [label=\"part \\\"2\\\"\\n750ns (75.0%)\""));
+        assert!(dot.contains("n0 -> n1;"));
+        assert!(dot.contains("n0 -> n2;"));
+        assert!(!dot.contains("n1 -> "));
+        assert!(dot.ends_with("}\n"));
+    }
+
+    #[cfg(feature = "tdigest")]
+    #[test]
+    fn diff_p95_regression() {
+        use crate::digest::Digest;
+
+        let mut flat = Digest::default();
+        let mut tail = Digest::default();
+        for i in 0..100 {
+            flat.record(1000);
+            tail.record(if i % 10 == 0 { 5500 } else { 500 });
+        }
+        let mut baseline = ExecDuration::new("main", 100, 100_000, 100_000);
+        baseline.set_percentiles(Some(flat.snapshot()));
+        let mut current = ExecDuration::new("main", 100, 100_000, 100_000);
+        current.set_percentiles(Some(tail.snapshot()));
+
+        let list = crate::output::diff(&[baseline], &[current]);
+        assert_eq!(list[0].avg_change, 0.0);
+        assert_eq!(list[0].p95_baseline.unwrap().as_nanos(), 1000);
+        assert!(list[0].p95_change.unwrap() > 100.0);
+    }
+
+    #[test]
+    fn scaled() {
+        let mut r = ExecDuration::new("main", 2, 1000, 1000);
+        r.add(ExecDuration::new("part 1", 2, 400, 1000));
+        r.add(ExecDuration::new("part 2", 1, 600, 1000));
+
+        let s = r.scaled(2.0);
+        assert_eq!(s.get_total_duration().as_nanos(), 2000);
+        assert_eq!(s.get_exec_count(), 2);
+        assert_eq!(s.get_elements().len(), 2);
+        assert_eq!(s.get_elements()[0].get_total_duration().as_nanos(), 800);
+        assert_eq!(s.get_elements()[1].get_exec_count(), 1);
+        assert_eq!(s.get_elements()[1].get_exec_percent(), 60);
+        assert_eq!(s.get_elements()[1].get_depth(), 1);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_round_trip() {
+        let mut r = ExecDuration::new("main", 2, 1000, 1000);
+        r.add(ExecDuration::new("part 1", 2, 400, 1000));
+        r.add(ExecDuration::new("part 2", 1, 600, 1000));
+
+        let json = r.to_json().unwrap();
+        let v = ExecDuration::from_json(&json).unwrap();
+        assert_eq!(v, r);
+        assert_eq!(v.get_elements().len(), 2);
+        assert_eq!(v.get_elements()[1].get_name(), "part 2");
+        assert_eq!(v.get_elements()[1].get_exec_percent(), 60);
+        assert!(ExecDuration::from_json("{").is_err());
+    }
+
+    #[test]
+    fn trimmed_mean() {
+        let mut r = ExecDuration::new("main", 0, 0, 0);
+        assert_eq!(r.get_trimmed_mean(10.0), None);
+        let samples: Vec<u128> = (0..19)
+            .map(|i| 1_000_000 + i * 1000)
+            .chain([1_000_000_000])
+            .collect();
+        r.count = samples.len() as u64;
+        r.duration = samples.iter().sum();
+        r.total = r.duration;
+        r.set_samples(samples);
+
+        let trimmed = r.get_trimmed_mean(10.0).unwrap();
+        assert!(trimmed > Duration::from_millis(1) && trimmed < Duration::from_micros(1020));
+        assert!(r.get_avg_duration() > Duration::from_millis(50));
+        assert_eq!(r.get_trimmed_mean(0.0), Some(r.get_avg_duration()));
+    }
+
+    #[test]
+    fn harmonic_mean_throughput() {
+        let a = ExecDuration::new("a", 10, 1_000_000_000, 2_000_000_000);
+        let b = ExecDuration::new("b", 30, 1_000_000_000, 2_000_000_000);
+        let idle = ExecDuration::new("idle", 0, 0, 2_000_000_000);
+        let mean = crate::output::harmonic_mean_throughput(&[a.clone(), b, idle]);
+        assert!((mean - 15.0).abs() < 1e-9);
+
+        let instant = ExecDuration::new("instant", 5, 0, 0);
+        let mean = crate::output::harmonic_mean_throughput(&[a, instant.clone()]);
+        assert!((mean - 20.0).abs() < 1e-9);
+        assert_eq!(
+            crate::output::harmonic_mean_throughput(&[instant]),
+            f64::INFINITY
+        );
+        assert_eq!(crate::output::harmonic_mean_throughput(&[]), 0.0);
+    }
+
+    #[test]
+    fn leaf_and_branch() {
+        let r = ExecDuration::branch(
+            "main",
+            2,
+            1_000,
+            vec![
+                ExecDuration::leaf("part 1", 2, 400),
+                ExecDuration::leaf("part 2", 4, 600),
+            ],
+        );
+        assert_eq!(r.get_name(), "main");
+        assert_eq!(r.get_exec_count(), 2);
+        assert_eq!(r.get_total_duration(), Duration::from_nanos(1_000));
+        assert_eq!(r.get_exec_percent(), 100);
+
+        let elements = r.get_elements();
+        assert_eq!(elements.len(), 2);
+        assert_eq!(elements[0].get_name(), "part 1");
+        assert_eq!(elements[0].get_exec_count(), 2);
+        assert_eq!(elements[0].get_total_duration(), Duration::from_nanos(400));
+        assert_eq!(elements[0].get_exec_percent(), 40);
+        assert_eq!(elements[0].get_depth(), 1);
+        assert_eq!(elements[1].get_exec_count(), 4);
+        assert_eq!(elements[1].get_avg_duration(), Duration::from_nanos(150));
+        assert_eq!(elements[1].get_hit_rate(), 1.0);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_seconds() {
+        let mut r = ExecDuration::new("main", 2, 1_500_000_000, 1_500_000_000);
+        r.add(ExecDuration::new("part 1", 3, 123_456_789, 1_500_000_000));
+
+        let json: serde_json::Value = serde_json::from_str(&r.to_json_seconds(3).unwrap()).unwrap();
+        assert_eq!(json["total"], 1.5);
+        assert_eq!(json["avg"], 0.75);
+        assert_eq!(json["count"], 2);
+        assert_eq!(json["childs"][0]["name"], "part 1");
+        assert_eq!(json["childs"][0]["total"], 0.123);
+        assert_eq!(json["childs"][0]["avg"], 0.041);
+        assert!(r.to_json_seconds(3).unwrap().contains("\"total\":1.5,"));
+    }
+
+    #[test]
+    fn throughput() {
+        let r = ExecDuration::new("main", 500, 2_000_000_000, 2_000_000_000);
+        assert!((r.throughput() - 250.0).abs() < 1e-9);
+        assert_eq!(ExecDuration::new("main", 0, 0, 0).throughput(), 0.0);
+        assert_eq!(
+            ExecDuration::new("main", 3, 0, 0).throughput(),
+            f64::INFINITY
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn depth_serialized() {
+        let mut r = ExecDuration::new("main", 1, 1000, 1000);
+        r.add(ExecDuration::new("part 1", 1, 400, 1000));
+        let mut groups = crate::output::group_by(vec![r], |_| String::from("group"));
+        let group = groups.remove(0);
+
+        let json: serde_json::Value = serde_json::from_str(&group.to_json().unwrap()).unwrap();
+        assert_eq!(json["depth"], 0);
+        assert_eq!(json["childs"][0]["name"], "main");
+        assert_eq!(json["childs"][0]["depth"], 1);
+        assert_eq!(json["childs"][0]["childs"][0]["name"], "part 1");
+        assert_eq!(json["childs"][0]["childs"][0]["depth"], 2);
+    }
+
+    #[test]
+    fn normalized_without_tail() {
+        let mut with_tail = ExecDuration::new("main", 1, 1000, 1000);
+        with_tail.add(ExecDuration::new("part 1", 1, 600, 1000));
+        with_tail.add(ExecDuration::new(super::TAIL, 1, 400, 1000));
+        let mut without_tail = ExecDuration::new("main", 1, 1000, 1000);
+        without_tail.add(ExecDuration::new("part 1", 1, 1000, 1000));
+
+        let normalized = with_tail.normalized();
+        assert_eq!(normalized, without_tail.normalized());
+        assert_eq!(
+            normalized.to_string(),
+            without_tail.normalized().to_string()
+        );
     }
 }
@@ -0,0 +1,28 @@
+use crate::output::DurationUnit;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Time spent by the library registering and reporting probes, in nanoseconds
+static OVERHEAD: AtomicU64 = AtomicU64::new(0);
+
+/// Account the time elapsed since `begin` as instrumentation overhead
+pub(crate) fn add(begin: Instant) {
+    let nanos = begin.elapsed().as_nanos() as u64;
+    OVERHEAD.fetch_add(nanos, Ordering::Relaxed);
+}
+
+pub(crate) fn total() -> Duration {
+    Duration::from_nanos(OVERHEAD.load(Ordering::Relaxed))
+}
+
+pub(crate) fn reset() {
+    OVERHEAD.store(0, Ordering::Relaxed);
+}
+
+/// Ratio of the overhead to the `measured` duration, 0 when nothing was measured
+pub(crate) fn ratio(measured: DurationUnit) -> f64 {
+    if measured == 0 {
+        return 0.0;
+    }
+    total().as_nanos() as f64 / measured as f64
+}
@@ -0,0 +1,152 @@
+use crate::output::ExecDuration;
+use crate::ValidationError;
+use rustc_hash::FxHashMap as HashMap;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Duration;
+
+const POINT_LABEL: &str = "point";
+
+/// Unit of the exported durations, the suffix of the metric name
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Unit {
+    Seconds,
+    Milliseconds,
+    Microseconds,
+    Nanoseconds,
+}
+
+impl Unit {
+    fn parse(unit: &str) -> Option<Unit> {
+        match unit {
+            "seconds" => Some(Unit::Seconds),
+            "milliseconds" => Some(Unit::Milliseconds),
+            "microseconds" => Some(Unit::Microseconds),
+            "nanoseconds" => Some(Unit::Nanoseconds),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Unit::Seconds => "seconds",
+            Unit::Milliseconds => "milliseconds",
+            Unit::Microseconds => "microseconds",
+            Unit::Nanoseconds => "nanoseconds",
+        }
+    }
+
+    fn value(self, d: Duration) -> f64 {
+        match self {
+            Unit::Seconds => d.as_secs_f64(),
+            Unit::Milliseconds => d.as_secs_f64() * 1e3,
+            Unit::Microseconds => d.as_secs_f64() * 1e6,
+            Unit::Nanoseconds => d.as_nanos() as f64,
+        }
+    }
+}
+
+/// Help text and unit of the metric family of a probe, as declared with `exec_duration::describe`
+#[derive(Clone, Debug)]
+pub(crate) struct Descriptor {
+    help: String,
+    unit: Unit,
+}
+
+impl Descriptor {
+    pub(crate) fn new(help: &str, unit: &str) -> Result<Descriptor, ValidationError> {
+        let unit =
+            Unit::parse(unit).ok_or_else(|| ValidationError::UnknownUnit(unit.to_string()))?;
+        Ok(Descriptor {
+            help: help.to_string(),
+            unit,
+        })
+    }
+}
+
+/// Replace the characters not allowed in a metric name
+fn metric_name(name: &str) -> String {
+    let mut res: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if res.starts_with(|c: char| c.is_ascii_digit()) {
+        res.insert(0, '_');
+    }
+    res
+}
+
+fn escape(value: &str, quoted: bool) -> String {
+    let value = value.replace('\\', "\\\\").replace('\n', "\\n");
+    if quoted {
+        value.replace('"', "\\\"")
+    } else {
+        value
+    }
+}
+
+fn label_set(labels: &BTreeMap<String, String>, point: Option<&str>) -> String {
+    let labels: Vec<String> = labels
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .chain(point.map(|p| (POINT_LABEL, p)))
+        .map(|(k, v)| format!("{}=\"{}\"", metric_name(k), escape(v, true)))
+        .collect();
+    if labels.is_empty() {
+        return String::new();
+    }
+    format!("{{{}}}", labels.join(","))
+}
+
+/// Write the series of `r`, labeled as its probe `probe`
+fn samples(
+    out: &mut String,
+    family: &str,
+    unit: Unit,
+    probe: &ExecDuration,
+    r: &ExecDuration,
+    point: Option<&str>,
+) {
+    let labels = label_set(probe.get_labels(), point);
+    let sum = unit.value(r.get_total_duration());
+    let _ = writeln!(out, "{}_sum{} {}", family, labels, sum);
+    let _ = writeln!(out, "{}_count{} {}", family, labels, r.get_exec_count());
+}
+
+/// Render the text exposition format, one summary family per probe name holding a series per label set
+/// and per point. Families without descriptor are in seconds, with a generic help text and no unit metadata.
+pub(crate) fn export(
+    results: &[ExecDuration],
+    descriptors: &HashMap<String, Descriptor>,
+) -> String {
+    let mut families: BTreeMap<&str, Vec<&ExecDuration>> = BTreeMap::new();
+    for r in results.iter() {
+        families.entry(r.get_name()).or_default().push(r);
+    }
+
+    let mut out = String::new();
+    for (name, series) in families.iter() {
+        let descriptor = descriptors.get(*name);
+        let unit = descriptor.map_or(Unit::Seconds, |d| d.unit);
+        let family = format!("{}_{}", metric_name(name), unit.name());
+        match descriptor {
+            Some(d) => {
+                let _ = writeln!(out, "# HELP {} {}", family, escape(&d.help, false));
+                let _ = writeln!(out, "# TYPE {} summary", family);
+                let _ = writeln!(out, "# UNIT {} {}", family, unit.name());
+            }
+            None => {
+                let help = format!("Execution duration of the {} probe", name);
+                let _ = writeln!(out, "# HELP {} {}", family, escape(&help, false));
+                let _ = writeln!(out, "# TYPE {} summary", family);
+            }
+        }
+        for r in series.iter() {
+            samples(&mut out, &family, unit, r, r, None);
+            for part in r.get_elements().iter() {
+                samples(&mut out, &family, unit, r, part, Some(part.get_name()));
+            }
+        }
+    }
+    out
+}
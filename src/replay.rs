@@ -0,0 +1,67 @@
+use crate::output::DurationUnit;
+use crate::Point;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Single execution of a probe as captured by the collection side, for instance with `exec_duration::on_report`,
+/// to be aggregated later with `exec_duration::replay`
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::{Point, RawEvent};
+/// use std::sync::Mutex;
+/// use std::time::Duration;
+///
+/// static EVENTS: Mutex<Vec<RawEvent>> = Mutex::new(Vec::new());
+///
+/// exec_duration::on_report(|name: &str, total: Duration, points: &[Point]| {
+///     EVENTS.lock().unwrap().push(RawEvent::new(name, total, points.to_vec()));
+/// });
+/// ```
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RawEvent {
+    pub(crate) name: String,
+    pub(crate) labels: BTreeMap<String, String>,
+    pub(crate) duration: DurationUnit,
+    pub(crate) points: Vec<Point>,
+}
+
+impl RawEvent {
+    /// Create a new event, with no label
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration::{Point, RawEvent};
+    /// use std::time::Duration;
+    ///
+    /// let event = RawEvent::new(
+    ///     "main",
+    ///     Duration::from_millis(3),
+    ///     vec![Point::new("part 1", Duration::from_millis(3))],
+    /// );
+    /// ```
+    pub fn new(name: &str, duration: Duration, points: Vec<Point>) -> Self {
+        RawEvent {
+            name: name.to_string(),
+            labels: BTreeMap::new(),
+            duration: duration.as_nanos(),
+            points,
+        }
+    }
+
+    /// Set a label of the execution
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration::RawEvent;
+    /// use std::time::Duration;
+    ///
+    /// let event = RawEvent::new("http_request", Duration::from_millis(3), Vec::new()).label("method", "GET");
+    /// ```
+    pub fn label(mut self, key: &str, value: &str) -> Self {
+        self.labels.insert(key.to_string(), value.to_string());
+        self
+    }
+}
@@ -0,0 +1,40 @@
+use crate::output::DurationUnit;
+use std::hash::Hasher;
+
+/// Uniform sample of at most `capacity` recorded durations (reservoir sampling).
+/// Once full, the n-th duration replaces a retained one with probability `capacity / n`.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub(crate) struct Reservoir {
+    capacity: usize,
+    seen: u64,
+    samples: Vec<DurationUnit>,
+}
+
+impl Reservoir {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Reservoir {
+            capacity,
+            seen: 0,
+            samples: Vec::new(),
+        }
+    }
+
+    pub(crate) fn record(&mut self, duration: DurationUnit) {
+        self.seen += 1;
+        if self.samples.len() < self.capacity {
+            self.samples.push(duration);
+            return;
+        }
+        // a hash of the execution rank is random enough, and keeps the store free of rng state
+        let mut hasher = rustc_hash::FxHasher::default();
+        hasher.write_u64(self.seen);
+        let index = (hasher.finish() % self.seen) as usize;
+        if let Some(sample) = self.samples.get_mut(index) {
+            *sample = duration;
+        }
+    }
+
+    pub(crate) fn samples(&self) -> &[DurationUnit] {
+        &self.samples
+    }
+}
@@ -0,0 +1,29 @@
+use std::cell::RefCell;
+
+thread_local! {
+    static SESSIONS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Leaves the session when dropped, including on unwinding
+struct SessionGuard;
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        SESSIONS.with(|s| s.borrow_mut().pop());
+    }
+}
+
+/// Run `f` within the given session on the current thread
+pub(crate) fn with_session<F, R>(name: &str, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    SESSIONS.with(|s| s.borrow_mut().push(name.to_string()));
+    let _guard = SessionGuard;
+    f()
+}
+
+/// Sessions of the current thread, outermost first
+pub(crate) fn current() -> Vec<String> {
+    SESSIONS.with(|s| s.borrow().clone())
+}
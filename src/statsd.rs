@@ -0,0 +1,46 @@
+use crate::output::ExecDuration;
+use std::net::{ToSocketAddrs, UdpSocket};
+
+/// Replace the characters with a meaning in the StatsD protocol
+fn metric_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            ':' | '|' | '@' | '#' => '_',
+            c if c.is_whitespace() => '_',
+            c => c,
+        })
+        .collect()
+}
+
+fn timing(name: &str, r: &ExecDuration) -> String {
+    format!(
+        "{}:{:.3}|ms",
+        name,
+        r.get_total_duration().as_secs_f64() * 1000.0
+    )
+}
+
+/// Send one `timing` metric per probe and per point, errors are dropped
+pub(crate) fn export(addr: &str, results: &[ExecDuration]) {
+    let addr = match addr.to_socket_addrs().ok().and_then(|mut a| a.next()) {
+        Some(addr) => addr,
+        None => return,
+    };
+    let local = if addr.is_ipv4() {
+        "0.0.0.0:0"
+    } else {
+        "[::]:0"
+    };
+    let socket = match UdpSocket::bind(local) {
+        Ok(socket) => socket,
+        Err(_) => return,
+    };
+    for r in results.iter() {
+        let name = metric_name(r.get_name());
+        let _ = socket.send_to(timing(&name, r).as_bytes(), addr);
+        for part in r.get_elements().iter() {
+            let part_name = format!("{}.{}", name, metric_name(part.get_name()));
+            let _ = socket.send_to(timing(&part_name, part).as_bytes(), addr);
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use crate::output::ExecDuration;
+use crate::Point;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Storage backend of the aggregates, replacing the in-memory store once installed with `exec_duration::set_store`.
+/// Executions are handed to the store after sampling, aliasing, warmup and clamping,
+/// the store is then in charge of aggregating and persisting them.
+/// The queries (`fetch_results`, `fetch_top`, `probe_names`, `has_data`, ...) read the store aggregates
+/// through `fetch`. The rolling time window, the histogram buckets and the sample retention are features
+/// of the in-memory store: they are not applied to the executions handed to a custom store.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::output::ExecDuration;
+/// use exec_duration::{Point, Store};
+/// use std::collections::BTreeMap;
+/// use std::time::Duration;
+///
+/// struct Database;
+///
+/// impl Store for Database {
+///     fn report(&mut self, name: &str, _: &BTreeMap<String, String>, duration: Duration, _: &[Point]) {
+///         // INSERT INTO executions ...
+///     }
+///
+///     fn fetch(&self) -> Vec<ExecDuration> {
+///         // SELECT ... GROUP BY name
+///         Vec::new()
+///     }
+///
+///     fn reset(&mut self) {
+///         // DELETE FROM executions
+///     }
+/// }
+///
+/// exec_duration::set_store(Database);
+/// ```
+pub trait Store: Send {
+    /// Record one execution of the named probe, with its labels, total duration and points
+    fn report(
+        &mut self,
+        name: &str,
+        labels: &BTreeMap<String, String>,
+        duration: Duration,
+        points: &[Point],
+    );
+
+    /// Record `count` executions of the named probe aggregated elsewhere, lasting `duration` in total,
+    /// as merged by `exec_duration::bulk_import`. Reports each of them at the average duration by default.
+    fn import(&mut self, name: &str, count: u64, duration: Duration) {
+        let avg = Duration::from_nanos((duration.as_nanos() / count.max(1) as u128) as u64);
+        for _ in 0..count {
+            self.report(name, &BTreeMap::new(), avg, &[]);
+        }
+    }
+
+    /// Get the aggregates of all the probes
+    fn fetch(&self) -> Vec<ExecDuration>;
+
+    /// Discard all the aggregates, for the auto-drain mode
+    fn reset(&mut self);
+}
@@ -0,0 +1,75 @@
+use crate::config::Config;
+use crate::manager;
+use crate::output::ExecDuration;
+
+/// Whole state of a metrics collection: the collected metrics along with the settings and callbacks.
+/// Instances are exchanged with the global collection using `exec_duration::swap`.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ExecProbeManager;
+///
+/// let previous = exec_duration::swap(ExecProbeManager::new());
+/// for r in previous.fetch_results().iter() {
+///     println!("{}", r);
+/// }
+/// ```
+pub struct ExecProbeManager {
+    inner: manager::ExecProbeManager,
+}
+
+impl ExecProbeManager {
+    /// Create an empty collection, with the defaults read from the environment (see `exec_duration::init`)
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration::ExecProbeManager;
+    ///
+    /// let fresh = ExecProbeManager::new();
+    /// assert!(!fresh.has_data());
+    /// ```
+    pub fn new() -> Self {
+        ExecProbeManager {
+            inner: manager::ExecProbeManager::new(Config::from_env().unwrap_or_default()),
+        }
+    }
+
+    /// Fetch the execution metrics held by this collection
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration::ExecProbeManager;
+    ///
+    /// let fresh = ExecProbeManager::new();
+    /// assert!(fresh.fetch_results().is_empty());
+    /// ```
+    pub fn fetch_results(&self) -> Vec<ExecDuration> {
+        self.inner.fetch_results()
+    }
+
+    /// Check whether this collection holds at least one execution
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration::ExecProbeManager;
+    ///
+    /// assert!(!ExecProbeManager::new().has_data());
+    /// ```
+    pub fn has_data(&self) -> bool {
+        self.inner.has_data()
+    }
+}
+
+impl Default for ExecProbeManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Install `new` as the global collection, returns the previous one
+pub(crate) fn swap(new: ExecProbeManager) -> ExecProbeManager {
+    ExecProbeManager {
+        inner: manager::replace(new.inner),
+    }
+}
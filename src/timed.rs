@@ -0,0 +1,52 @@
+use crate::ExecProbe;
+
+/// Type whose operations are measured by a probe named after the type.
+/// Implement it using `#[derive(Timed)]`, which requires the `derive` feature, or by hand.
+///
+/// # Examples
+/// ```
+/// use exec_duration::Timed;
+///
+/// struct Compaction;
+///
+/// impl Timed for Compaction {
+///     fn probe_name() -> &'static str {
+///         "Compaction"
+///     }
+/// }
+///
+/// let merged = Compaction.timed_run(|_| 42);
+/// assert_eq!(merged, 42);
+/// ```
+pub trait Timed: Sized {
+    /// Name of the probe measuring the operations of this type
+    fn probe_name() -> &'static str;
+
+    /// Run `f` on this value, the execution is reported as an execution of the probe `probe_name`
+    ///
+    /// # Examples
+    /// ```
+    /// use exec_duration::Timed;
+    ///
+    /// struct Compaction;
+    ///
+    /// impl Timed for Compaction {
+    ///     fn probe_name() -> &'static str {
+    ///         "Compaction"
+    ///     }
+    /// }
+    ///
+    /// Compaction.timed_run(|_| {
+    ///     // code
+    /// });
+    /// ```
+    fn timed_run<R, F>(self, f: F) -> R
+    where
+        F: FnOnce(Self) -> R,
+    {
+        let _ep = ExecProbe::builder(Self::probe_name())
+            .record_points(false)
+            .build();
+        f(self)
+    }
+}
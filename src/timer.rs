@@ -0,0 +1,101 @@
+use crate::clock;
+use crate::clock::Clock;
+use crate::manager::Point;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// Spans of the sub-timers of a probe, shared with the guards still running.
+/// Being transient, they never take part in comparisons nor in hashing.
+#[derive(Clone, Default)]
+pub(crate) struct Timers(Arc<Mutex<Vec<Point>>>);
+
+impl Timers {
+    /// Take the spans of the sub-timers dropped so far
+    pub(crate) fn take(&self) -> Vec<Point> {
+        std::mem::take(&mut *self.0.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+impl fmt::Debug for Timers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Timers")
+    }
+}
+
+impl PartialEq for Timers {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for Timers {}
+
+impl PartialOrd for Timers {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Timers {
+    fn cmp(&self, _: &Self) -> Ordering {
+        Ordering::Equal
+    }
+}
+
+impl Hash for Timers {
+    fn hash<H: Hasher>(&self, _: &mut H) {}
+}
+
+/// Independent timer within a probe.
+/// Instances are created using `ExecProbe::timer`, the span from the creation to the drop is reported as an element
+/// of the probe. Unlike points, sub-timers may overlap, they must be dropped before the probe is stopped to be
+/// reported and they are not affected by `ExecProbe::pause`.
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::ExecProbe;
+///
+/// let mut ep = ExecProbe::new("main");
+/// let download = ep.timer("download");
+/// let decode = ep.timer("decode");
+/// // code
+/// drop(download);
+/// drop(decode);
+/// ```
+#[derive(Debug)]
+#[must_use = "the span is reported as soon as the guard is dropped"]
+pub struct SubTimer {
+    name: String,
+    clock: Clock,
+    begin: SystemTime,
+    timers: Timers,
+}
+
+impl SubTimer {
+    pub(crate) fn new(name: &str, clock: Clock, timers: Timers) -> Self {
+        SubTimer {
+            name: name.to_string(),
+            clock,
+            begin: clock.now(),
+            timers,
+        }
+    }
+}
+
+impl Drop for SubTimer {
+    fn drop(&mut self) {
+        let now = self.clock.now();
+        if let Some((d, _)) = clock::anomaly_policy().elapsed(self.begin, now) {
+            let mut timers = self.timers.0.lock().unwrap_or_else(|e| e.into_inner());
+            timers.push(Point {
+                name: std::mem::take(&mut self.name),
+                duration: d.as_nanos(),
+                hits: 1,
+            });
+        }
+    }
+}
@@ -0,0 +1,25 @@
+/// Distributed tracing context of an execution, to correlate the metrics with the traces
+///
+/// # Examples
+/// ```
+/// use exec_duration;
+/// use exec_duration::{ExecProbe, Point, TraceContext};
+/// use std::time::Duration;
+///
+/// exec_duration::on_report_traced(|name: &str, _: Duration, _: &[Point], ctx: Option<&TraceContext>| {
+///     if let Some(ctx) = ctx {
+///         println!("[{}] trace {:032x} span {:016x}", name, ctx.trace_id, ctx.span_id);
+///     }
+/// });
+///
+/// let mut ep = ExecProbe::with_trace_context("handler", 0x4bf92f3577b34da6a3ce929d0e0e4736, 0x00f067aa0ba902b7);
+/// ep.add_point("line 1");
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TraceContext {
+    /// Trace id
+    pub trace_id: u128,
+    /// Span id
+    pub span_id: u64,
+}
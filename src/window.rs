@@ -0,0 +1,74 @@
+use crate::output::DurationUnit;
+use std::time::{Duration, Instant};
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+struct Bucket {
+    index: u64,
+    count: u64,
+    duration: DurationUnit,
+}
+
+/// Ring of time buckets, a bucket is recycled when its slot is reused by a more recent time index
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub(crate) struct Ring {
+    buckets: Vec<Bucket>,
+}
+
+impl Ring {
+    pub(crate) fn record(&mut self, config: &WindowConfig, duration: DurationUnit) {
+        if self.buckets.len() != config.size {
+            self.buckets = vec![Bucket::default(); config.size];
+        }
+        let index = config.current_index();
+        let bucket = &mut self.buckets[(index % config.size as u64) as usize];
+        if bucket.index != index {
+            *bucket = Bucket {
+                index,
+                ..Default::default()
+            };
+        }
+        bucket.count += 1;
+        bucket.duration += duration;
+    }
+
+    /// Sum the buckets overlapping the last `window`
+    pub(crate) fn sum(&self, config: &WindowConfig, window: Duration) -> (u64, DurationUnit) {
+        let current = config.current_index();
+        let span = config.span(window);
+        self.buckets
+            .iter()
+            .filter(|b| b.count > 0 && b.index <= current && current - b.index < span)
+            .fold((0, 0), |(c, d), b| (c + b.count, d + b.duration))
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.buckets.clear();
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub(crate) struct WindowConfig {
+    width: Duration,
+    size: usize,
+    origin: Instant,
+}
+
+impl WindowConfig {
+    pub(crate) fn new(width: Duration, size: usize) -> Self {
+        WindowConfig {
+            width: width.max(Duration::from_nanos(1)),
+            size: size.max(1),
+            origin: Instant::now(),
+        }
+    }
+
+    fn current_index(&self) -> u64 {
+        (self.origin.elapsed().as_nanos() / self.width.as_nanos()) as u64
+    }
+
+    /// Number of buckets overlapping `window`, at most the ring size
+    fn span(&self, window: Duration) -> u64 {
+        let n = window.as_nanos().div_ceil(self.width.as_nanos());
+        n.clamp(1, self.size as u128) as u64
+    }
+}
@@ -0,0 +1,28 @@
+use exec_duration::{CustomAgg, ExecProbe};
+use std::thread::sleep;
+use std::time::Duration;
+
+const NAME: &str = "aggregated";
+
+#[test]
+fn count_samples_over_1ms() {
+    exec_duration::set_aggregator(NAME, |agg: &mut CustomAgg, duration| {
+        if duration > 1_000_000 {
+            *agg.get_or_default::<u64>() += 1;
+        }
+    });
+
+    for i in 0..5 {
+        let mut ep = ExecProbe::new(NAME);
+        if i % 2 == 0 {
+            sleep(Duration::from_millis(2));
+        }
+        ep.add_point("part 1");
+    }
+
+    let list = exec_duration::fetch_results();
+    let r = list.iter().find(|r| r.get_name() == NAME).unwrap();
+    assert_eq!(r.get_exec_count(), 5);
+    let over = r.get_custom().and_then(|c| c.downcast_ref::<u64>());
+    assert_eq!(over, Some(&3));
+}
@@ -0,0 +1,30 @@
+use exec_duration::{ExecProbe, ValidationError};
+
+fn probe(name: &str) {
+    let mut ep = ExecProbe::new(name);
+    ep.add_point("line 1");
+}
+
+#[test]
+fn aliases() {
+    exec_duration::add_alias("load", "load_conf").unwrap();
+    exec_duration::add_alias("load_conf", "load_config").unwrap();
+    assert_eq!(
+        exec_duration::add_alias("load_config", "load"),
+        Err(ValidationError::AliasCycle(String::from("load_config")))
+    );
+    assert_eq!(
+        exec_duration::add_alias("serve", "serve"),
+        Err(ValidationError::AliasCycle(String::from("serve")))
+    );
+
+    probe("load");
+    probe("load_conf");
+    probe("load_config");
+
+    let list = exec_duration::fetch_results();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].get_name(), "load_config");
+    assert_eq!(list[0].get_exec_count(), 3);
+    assert_eq!(list[0].get_elements()[0].get_exec_count(), 3);
+}
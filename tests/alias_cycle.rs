@@ -0,0 +1,23 @@
+use exec_duration::{ExecProbe, ValidationError};
+
+fn probe(name: &str) {
+    let mut ep = ExecProbe::new(name);
+    ep.add_point("line 1");
+}
+
+#[test]
+fn repointed_alias_cycle() {
+    exec_duration::add_alias("a", "b").unwrap();
+    exec_duration::add_alias("x", "a").unwrap();
+    assert_eq!(
+        exec_duration::add_alias("a", "x"),
+        Err(ValidationError::AliasCycle(String::from("a")))
+    );
+    // re-pointing without cycle is allowed
+    exec_duration::add_alias("a", "c").unwrap();
+
+    probe("x");
+    let list = exec_duration::fetch_results();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].get_name(), "c");
+}
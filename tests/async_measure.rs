@@ -0,0 +1,32 @@
+use std::time::Duration;
+use tokio::time::sleep;
+
+const NAME: &str = "handler";
+
+async fn handler() {
+    let mut m = exec_duration::measure_async(NAME);
+    sleep(Duration::from_millis(20)).await;
+    m.checkpoint("read");
+    tokio::task::yield_now().await;
+    sleep(Duration::from_millis(10)).await;
+    m.checkpoint("write");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn checkpoints_across_await_points() {
+    tokio::spawn(handler()).await.unwrap();
+
+    let list = exec_duration::fetch_results();
+    let r = list.iter().find(|r| r.get_name() == NAME).unwrap();
+    assert_eq!(r.get_exec_count(), 1);
+    assert!(r.get_total_duration() >= Duration::from_millis(30));
+    let names: Vec<&str> = r
+        .get_elements()
+        .iter()
+        .map(|e| e.get_name())
+        .filter(|name| *name != exec_duration::output::TAIL)
+        .collect();
+    assert_eq!(names, ["read", "write"]);
+    assert!(r.get_elements()[0].get_total_duration() >= Duration::from_millis(20));
+    assert!(r.get_elements()[1].get_total_duration() >= Duration::from_millis(10));
+}
@@ -0,0 +1,31 @@
+use exec_duration::ExecProbe;
+
+fn probe(name: &str) {
+    let mut ep = ExecProbe::new(name);
+    ep.add_point("part 1");
+}
+
+#[test]
+fn no_double_count() {
+    exec_duration::set_auto_drain(true);
+    let handle = exec_duration::register_fast("fast");
+    probe("main");
+    probe("main");
+    drop(handle.start());
+
+    let list = exec_duration::fetch_results();
+    assert_eq!(list.len(), 2);
+    let main = list.iter().find(|r| r.get_name() == "main").unwrap();
+    assert_eq!(main.get_exec_count(), 2);
+    assert!(exec_duration::fetch_results().is_empty());
+
+    probe("main");
+    let list = exec_duration::fetch_results();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].get_exec_count(), 1);
+
+    exec_duration::set_auto_drain(false);
+    probe("main");
+    assert_eq!(exec_duration::fetch_results().len(), 1);
+    assert_eq!(exec_duration::fetch_results().len(), 1);
+}
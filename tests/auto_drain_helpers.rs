@@ -0,0 +1,21 @@
+use exec_duration::ExecProbe;
+
+#[test]
+fn helpers_do_not_drain() {
+    exec_duration::set_auto_drain(true);
+    let mut ep = ExecProbe::new("main");
+    ep.add_point("part 1");
+    ep.stop();
+
+    assert_eq!(exec_duration::fetch_hotspots(0.0).len(), 1);
+    assert_eq!(exec_duration::fetch_results_where(&[]).len(), 1);
+    assert_eq!(exec_duration::fetch_results_normalized().len(), 1);
+    assert_eq!(
+        exec_duration::fetch_grouped_by(|r| r.get_name().to_string()).len(),
+        1
+    );
+
+    // only the explicit fetch drains
+    assert_eq!(exec_duration::fetch_results()[0].get_exec_count(), 1);
+    assert!(exec_duration::fetch_results().is_empty());
+}
@@ -0,0 +1,25 @@
+use exec_duration::ExecProbe;
+use std::thread::sleep;
+use std::time::Duration;
+
+#[test]
+fn over_budget() {
+    exec_duration::set_budget("render", Duration::from_millis(10));
+    exec_duration::set_budget("idle", Duration::from_millis(10));
+    for name in ["render", "idle", "unbudgeted"].iter() {
+        let mut ep = ExecProbe::new(name);
+        if *name == "render" {
+            sleep(Duration::from_millis(50));
+        }
+        ep.add_point("line 1");
+    }
+
+    let list = exec_duration::fetch_results();
+    let get = |name: &str| list.iter().find(|r| r.get_name() == name).unwrap();
+    let render = get("render");
+    assert_eq!(render.budget(), Some(Duration::from_millis(10)));
+    assert!(render.is_over_budget());
+    assert!(!get("idle").is_over_budget());
+    assert_eq!(get("unbudgeted").budget(), None);
+    assert!(!get("unbudgeted").is_over_budget());
+}
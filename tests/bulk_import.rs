@@ -0,0 +1,28 @@
+use std::time::Duration;
+
+#[test]
+fn imported_aggregates() {
+    let entries: Vec<(String, u64, Duration)> = (0..1000u64)
+        .map(|i| {
+            let name = format!("probe {}", i % 10);
+            (name, i % 3 + 1, Duration::from_micros(i + 1))
+        })
+        .chain(std::iter::once((
+            String::from("empty"),
+            0,
+            Duration::from_secs(1),
+        )))
+        .collect();
+    exec_duration::bulk_import(&entries);
+
+    let list = exec_duration::fetch_results();
+    assert_eq!(list.len(), 10);
+    for r in list.iter() {
+        let expected = entries.iter().filter(|e| e.0 == r.get_name());
+        let count: u64 = expected.clone().map(|e| e.1).sum();
+        let total: Duration = expected.map(|e| e.2).sum();
+        assert_eq!(r.get_exec_count(), count);
+        assert_eq!(r.get_total_duration(), total);
+        assert!(r.get_elements().is_empty());
+    }
+}
@@ -0,0 +1,39 @@
+use std::thread::sleep;
+use std::time::Duration;
+
+#[test]
+fn nested_calls() {
+    for _ in 0..2 {
+        exec_duration::enter("a");
+        exec_duration::enter("b");
+        sleep(Duration::from_millis(2));
+        exec_duration::enter("c");
+        exec_duration::exit();
+        exec_duration::exit();
+        exec_duration::exit();
+    }
+    exec_duration::enter("b");
+    exec_duration::exit();
+    // unbalanced exits are ignored
+    exec_duration::exit();
+
+    let mut list = exec_duration::fetch_results();
+    list.sort_by(|x, y| x.get_name().cmp(y.get_name()));
+    let names: Vec<&str> = list.iter().map(|r| r.get_name()).collect();
+    assert_eq!(names, vec!["a", "b"]);
+
+    let a = &list[0];
+    assert_eq!(a.get_exec_count(), 2);
+    let b = &a.get_elements()[0];
+    assert_eq!(b.get_name(), "b");
+    assert_eq!(b.get_exec_count(), 2);
+    assert_eq!(b.get_depth(), 1);
+    assert!(b.get_total_duration() >= Duration::from_millis(4));
+    assert!(b.get_total_duration() <= a.get_total_duration());
+    assert_eq!(b.get_elements()[0].get_name(), "c");
+    assert_eq!(b.get_elements()[0].get_depth(), 2);
+
+    // the top-level call of b is recorded apart
+    assert_eq!(list[1].get_exec_count(), 1);
+    assert!(list[1].get_elements().is_empty());
+}
@@ -0,0 +1,24 @@
+use exec_duration::ExecProbe;
+use std::thread::sleep;
+use std::time::Duration;
+
+#[test]
+fn absurd_sample_is_clamped() {
+    exec_duration::set_sample_clamp(Duration::from_millis(20));
+    {
+        let mut ep = ExecProbe::new("main");
+        ep.add_point("line 1");
+    }
+    {
+        let mut ep = ExecProbe::new("main");
+        sleep(Duration::from_millis(50));
+        ep.add_point("line 1");
+    }
+
+    let list = exec_duration::fetch_results();
+    let r = list.iter().find(|r| r.get_name() == "main").unwrap();
+    assert_eq!(r.get_exec_count(), 1);
+    assert_eq!(r.get_clamped_count(), 1);
+    assert!(r.get_total_duration() < Duration::from_millis(20));
+    assert_eq!(r.get_elements()[0].get_exec_count(), 1);
+}
@@ -0,0 +1,22 @@
+#![cfg(target_os = "linux")]
+
+use exec_duration::{Clock, ExecProbe};
+use std::thread::sleep;
+use std::time::Duration;
+
+const NAME: &str = "coarse";
+
+#[test]
+fn sane_totals() {
+    let mut ep = ExecProbe::builder(NAME).clock(Clock::Coarse).build();
+    sleep(Duration::from_millis(100));
+    ep.add_point("part 1");
+    ep.stop();
+
+    let list = exec_duration::fetch_results();
+    let r = list.iter().find(|r| r.get_name() == NAME).unwrap();
+    // coarse clock resolution is a few milliseconds
+    assert!(r.get_total_duration() >= Duration::from_millis(90));
+    assert!(r.get_total_duration() < Duration::from_millis(200));
+    assert!(r.get_elements()[0].get_total_duration() >= Duration::from_millis(90));
+}
@@ -0,0 +1,24 @@
+#![cfg(feature = "color")]
+
+use exec_duration::output;
+use exec_duration::ExecProbe;
+
+#[test]
+fn no_escape_codes_without_terminal() {
+    {
+        let mut ep = ExecProbe::new("main");
+        ep.add_point("part 1");
+    }
+    let list = exec_duration::fetch_results();
+
+    let mut out = Vec::new();
+    output::write_summary_colored(&mut out, &list, false).unwrap();
+    let text = String::from_utf8(out).unwrap();
+    assert!(text.starts_with("[main] 100.0% Call: 1"));
+    assert!(text.contains("  [part 1]"));
+    assert!(!text.contains('\u{1b}'));
+
+    let mut out = Vec::new();
+    output::write_summary_colored(&mut out, &list, true).unwrap();
+    assert!(String::from_utf8(out).unwrap().contains('\u{1b}'));
+}
@@ -0,0 +1,38 @@
+use exec_duration::ExecProbe;
+
+fn nested(expected: Option<&str>) {
+    let mut ep = ExecProbe::new("nested");
+    assert_eq!(ep.correlation_id(), expected);
+    ep.add_point("line 1");
+}
+
+#[test]
+fn nested_probe_inherits_id() {
+    assert_eq!(ExecProbe::new("before").correlation_id(), None);
+    {
+        let _id = exec_duration::set_correlation_id("req-1");
+        let mut ep = ExecProbe::new("handler");
+        assert_eq!(ep.correlation_id(), Some("req-1"));
+        nested(Some("req-1"));
+        {
+            let _inner = exec_duration::set_correlation_id("req-2");
+            nested(Some("req-2"));
+        }
+        nested(Some("req-1"));
+        ep.add_point("nested");
+
+        let other =
+            std::thread::spawn(|| ExecProbe::new("other").correlation_id().map(String::from));
+        assert_eq!(other.join().unwrap(), None);
+    }
+    nested(None);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn serialized_id() {
+    let _id = exec_duration::set_correlation_id("req-3");
+    let ep = ExecProbe::new("handler");
+    let json = serde_json::to_string(&ep).unwrap();
+    assert!(json.contains("\"correlation_id\":\"req-3\""), "{}", json);
+}
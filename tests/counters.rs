@@ -0,0 +1,20 @@
+use exec_duration::ExecProbe;
+
+const NAME: &str = "counted";
+
+#[test]
+fn counters_aggregate() {
+    for bytes in &[100, 50, 25] {
+        let mut ep = ExecProbe::new(NAME);
+        ep.add_counter("bytes", *bytes);
+        ep.add_counter("calls", 1);
+        ep.add_point("part 1");
+    }
+
+    let list = exec_duration::fetch_results();
+    let r = list.iter().find(|r| r.get_name() == NAME).unwrap();
+    assert_eq!(r.get_counters().get("bytes"), Some(&175));
+    assert_eq!(r.get_counters().get("calls"), Some(&3));
+    assert_eq!(r.get_counters().len(), 2);
+    assert!(r.get_elements()[0].get_counters().is_empty());
+}
@@ -0,0 +1,30 @@
+#![cfg(feature = "criterion")]
+
+use criterion::measurement::{Measurement, WallTime};
+use exec_duration::output::ExecDuration;
+use std::time::Duration;
+
+#[test]
+fn measurement_values() {
+    let results = vec![
+        ExecDuration::builder("parse")
+            .count(10)
+            .total(Duration::from_millis(25))
+            .build()
+            .unwrap(),
+        ExecDuration::builder("render")
+            .count(5)
+            .total(Duration::from_micros(1500))
+            .build()
+            .unwrap(),
+    ];
+
+    let values = exec_duration::to_criterion_measurements(&results);
+    assert_eq!(values.len(), 2);
+    assert_eq!(values[0].0, "parse");
+    assert_eq!(WallTime.to_f64(&values[0].1), 25_000_000.0);
+    assert_eq!(values[1].0, "render");
+    assert_eq!(WallTime.to_f64(&values[1].1), 1_500_000.0);
+    let sum = WallTime.add(&values[0].1, &values[1].1);
+    assert_eq!(WallTime.to_f64(&sum), 26_500_000.0);
+}
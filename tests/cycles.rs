@@ -0,0 +1,21 @@
+#![cfg(all(feature = "cycles", target_arch = "x86_64"))]
+
+use exec_duration::{Clock, ExecProbe};
+use std::time::{Duration, Instant};
+
+#[test]
+fn busy_loop() {
+    {
+        let mut ep = ExecProbe::builder("busy").clock(Clock::Cycles).build();
+        let begin = Instant::now();
+        while begin.elapsed() < Duration::from_millis(20) {
+            std::hint::spin_loop();
+        }
+        ep.add_point("spin");
+    }
+
+    let list = exec_duration::fetch_results();
+    let d = list[0].get_total_duration();
+    assert!(d >= Duration::from_millis(10), "{:?}", d);
+    assert!(d < Duration::from_secs(1), "{:?}", d);
+}
@@ -0,0 +1,29 @@
+use exec_duration::ExecProbe;
+use std::thread::sleep;
+use std::time::Duration;
+
+#[test]
+fn overdue_probe_is_abandoned() {
+    let deadline = Duration::from_millis(20);
+    {
+        let mut ep = ExecProbe::builder("request").deadline(deadline).build();
+        ep.add_point("part 1");
+    }
+    let mut late = ExecProbe::builder("request").deadline(deadline).build();
+    let leaked = ExecProbe::builder("leaked").deadline(deadline).build();
+    sleep(Duration::from_millis(30));
+    exec_duration::flush_live();
+
+    let list = exec_duration::fetch_results();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].get_exec_count(), 1);
+    assert_eq!(list[0].get_provisional_count(), 0);
+
+    late.add_point("part 1");
+    late.stop();
+    std::mem::forget(leaked);
+    let list = exec_duration::fetch_results();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].get_exec_count(), 1);
+    assert!(list[0].get_total_duration() < deadline);
+}
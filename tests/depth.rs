@@ -0,0 +1,29 @@
+use exec_duration::ExecProbe;
+
+const NAME: &str = "recursion";
+
+fn recursive(n: usize) {
+    let _guard = exec_duration::depth_guard();
+    if n > 1 {
+        recursive(n - 1);
+    }
+}
+
+#[test]
+fn max_depth_matches_recursion() {
+    for n in &[3, 7, 5] {
+        let mut ep = ExecProbe::new(NAME);
+        recursive(*n);
+        ep.add_point("recursive");
+    }
+    {
+        let mut ep = ExecProbe::new("flat");
+        ep.add_point("part 1");
+    }
+
+    let list = exec_duration::fetch_results();
+    let r = list.iter().find(|r| r.get_name() == NAME).unwrap();
+    assert_eq!(r.get_max_depth(), 7);
+    let r = list.iter().find(|r| r.get_name() == "flat").unwrap();
+    assert_eq!(r.get_max_depth(), 0);
+}
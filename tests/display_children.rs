@@ -0,0 +1,29 @@
+use exec_duration::output;
+use exec_duration::output::ExecDuration;
+use std::time::Duration;
+
+#[test]
+fn max_display_children() {
+    let mut builder = ExecDuration::builder("main")
+        .count(1)
+        .total(Duration::from_millis(100));
+    for i in 0..10 {
+        let child = ExecDuration::builder(&format!("part {}", i))
+            .count(1)
+            .total(Duration::from_millis(i))
+            .build()
+            .unwrap();
+        builder = builder.child(child);
+    }
+    let r = builder.build().unwrap();
+    assert_eq!(r.to_string().lines().count(), 11);
+
+    output::set_max_display_children(3);
+    let text = r.to_string();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 5);
+    assert!(lines[1].starts_with("[main] [part 7]"), "{}", lines[1]);
+    assert!(lines[2].starts_with("[main] [part 8]"), "{}", lines[2]);
+    assert!(lines[3].starts_with("[main] [part 9]"), "{}", lines[3]);
+    assert_eq!(lines[4], "[main] (+7 more)");
+}
@@ -0,0 +1,28 @@
+use exec_duration::output::TAIL;
+use exec_duration::ExecProbe;
+
+#[test]
+fn first_insertion_order() {
+    {
+        let mut ep = ExecProbe::new("main");
+        ep.add_point("zebra");
+        ep.add_point("alpha");
+        ep.add_point("mango");
+    }
+    {
+        // later executions do not reorder the points seen first
+        let mut ep = ExecProbe::new("main");
+        ep.add_point("mango");
+        ep.add_point("beta");
+        ep.add_point("zebra");
+    }
+
+    let list = exec_duration::fetch_results();
+    let names: Vec<&str> = list[0]
+        .get_elements()
+        .iter()
+        .map(|e| e.get_name())
+        .filter(|name| *name != TAIL)
+        .collect();
+    assert_eq!(names, vec!["zebra", "alpha", "mango", "beta"]);
+}
@@ -0,0 +1,36 @@
+use exec_duration::{ExecProbe, InitError};
+use std::env;
+
+#[test]
+fn disabled_by_env() {
+    env::set_var("EXEC_DURATION_SAMPLE_RATE", "2");
+    assert_eq!(
+        exec_duration::init(),
+        Err(InitError::InvalidEnv(String::from(
+            "EXEC_DURATION_SAMPLE_RATE"
+        )))
+    );
+    env::remove_var("EXEC_DURATION_SAMPLE_RATE");
+
+    env::set_var("EXEC_DURATION_ENABLED", "false");
+    assert_eq!(exec_duration::init(), Ok(()));
+    {
+        let mut ep = ExecProbe::new("main");
+        ep.add_point("part 1");
+    }
+    assert!(exec_duration::fetch_results().is_empty());
+
+    exec_duration::shutdown();
+    env::remove_var("EXEC_DURATION_ENABLED");
+    env::set_var("EXEC_DURATION_SAMPLE_RATE", "0.5");
+    env::set_var("EXEC_DURATION_MAX_PROBES", "1");
+    assert_eq!(exec_duration::init(), Ok(()));
+    for name in ["main", "main", "other", "other", "main", "main"].iter() {
+        let mut ep = ExecProbe::new(name);
+        ep.add_point("part 1");
+    }
+    let list = exec_duration::fetch_results();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].get_name(), "main");
+    assert_eq!(list[0].get_exec_count(), 2);
+}
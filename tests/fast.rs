@@ -0,0 +1,31 @@
+use std::thread;
+use std::time::Duration;
+
+const NAME: &str = "fast";
+const THREADS: u64 = 8;
+const ITERATIONS: u64 = 10_000;
+
+#[test]
+fn no_lost_counts() {
+    let handle = exec_duration::register_fast(NAME);
+    let threads: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let handle = handle.clone();
+            thread::spawn(move || {
+                for _ in 0..ITERATIONS {
+                    handle.record(Duration::from_nanos(10));
+                    let _probe = handle.start();
+                }
+            })
+        })
+        .collect();
+    for t in threads {
+        t.join().unwrap();
+    }
+
+    let list = exec_duration::fetch_results();
+    let r = list.iter().find(|r| r.get_name() == NAME).unwrap();
+    assert_eq!(r.get_exec_count(), THREADS * ITERATIONS * 2);
+    assert!(r.get_total_duration() >= Duration::from_nanos(10 * THREADS * ITERATIONS));
+    assert_eq!(exec_duration::probe_names(), vec![NAME]);
+}
@@ -0,0 +1,28 @@
+use exec_duration::ExecProbe;
+
+#[test]
+fn fetch_results_into() {
+    for name in ["a", "b"].iter() {
+        let mut ep = ExecProbe::new(name);
+        ep.add_point("part 1");
+    }
+    let mut buf = Vec::with_capacity(16);
+    let ptr = buf.as_ptr();
+    exec_duration::fetch_results_into(&mut buf);
+    assert_eq!(buf.len(), 2);
+    assert_eq!(buf.capacity(), 16);
+    assert_eq!(buf.as_ptr(), ptr);
+
+    {
+        let mut ep = ExecProbe::new("c");
+        ep.add_point("part 1");
+    }
+    exec_duration::fetch_results_into(&mut buf);
+    assert_eq!(buf.len(), 3);
+    assert_eq!(buf.capacity(), 16);
+    assert_eq!(buf.as_ptr(), ptr);
+
+    let mut names: Vec<&str> = buf.iter().map(|r| r.get_name()).collect();
+    names.sort_unstable();
+    assert_eq!(names, vec!["a", "b", "c"]);
+}
@@ -0,0 +1,24 @@
+#![cfg(feature = "ffi")]
+
+use exec_duration::ffi::{exec_duration_start, exec_duration_stop};
+use std::ffi::CString;
+use std::ptr;
+
+#[test]
+fn extern_interface() {
+    let name = CString::new("c_callback").unwrap();
+    let handle = unsafe { exec_duration_start(name.as_ptr()) };
+    assert_ne!(handle, 0);
+    exec_duration_stop(handle);
+    exec_duration_stop(handle);
+
+    assert_eq!(unsafe { exec_duration_start(ptr::null()) }, 0);
+    let invalid = b"\xff\xfe\0";
+    assert_eq!(unsafe { exec_duration_start(invalid.as_ptr().cast()) }, 0);
+    exec_duration_stop(0);
+
+    let list = exec_duration::fetch_results();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].get_name(), "c_callback");
+    assert_eq!(list[0].get_exec_count(), 1);
+}
@@ -0,0 +1,32 @@
+use exec_duration::ExecProbe;
+use std::thread::sleep;
+use std::time::Duration;
+
+#[test]
+fn provisional_entry() {
+    exec_duration::set_live_tracking(true);
+    {
+        let mut ep = ExecProbe::new("event_loop");
+        ep.add_point("iteration");
+    }
+    let mut ep = ExecProbe::new("event_loop");
+    let other = ExecProbe::new("idle");
+    sleep(Duration::from_millis(20));
+    exec_duration::flush_live();
+
+    let list = exec_duration::fetch_results();
+    let r = list.iter().find(|r| r.get_name() == "event_loop").unwrap();
+    assert_eq!(r.get_exec_count(), 2);
+    assert_eq!(r.get_provisional_count(), 1);
+    assert!(r.get_total_duration() >= Duration::from_millis(20));
+    let idle = list.iter().find(|r| r.get_name() == "idle").unwrap();
+    assert_eq!(idle.get_provisional_count(), 1);
+
+    ep.add_point("iteration");
+    ep.stop();
+    drop(other);
+    let list = exec_duration::fetch_results();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].get_exec_count(), 2);
+    assert_eq!(list[0].get_provisional_count(), 0);
+}
@@ -0,0 +1,21 @@
+use exec_duration::ExecProbe;
+use std::thread::sleep;
+use std::time::Duration;
+
+#[test]
+fn gap_between_probes() {
+    assert_eq!(exec_duration::last_gap(), None);
+    let mut ep = ExecProbe::new("first");
+    ep.add_point("part 1");
+    ep.stop();
+    assert_eq!(exec_duration::last_gap(), None);
+
+    sleep(Duration::from_millis(20));
+    let token = exec_duration::start("detached");
+    exec_duration::finish(token);
+    let mut ep = ExecProbe::new("second");
+    let gap = exec_duration::last_gap().unwrap();
+    assert!(gap >= Duration::from_millis(20));
+    assert!(gap < Duration::from_millis(200));
+    ep.add_point("part 1");
+}
@@ -0,0 +1,39 @@
+use exec_duration::ExecProbe;
+use std::thread::sleep;
+use std::time::Duration;
+
+fn probe(name: &str, ms: u64) {
+    let mut ep = ExecProbe::new(name);
+    sleep(Duration::from_millis(ms));
+    ep.add_point("part 1");
+}
+
+#[test]
+fn group_by_prefix() {
+    probe("db.a", 10);
+    probe("db.b", 20);
+    probe("http.c", 5);
+
+    let mut list = exec_duration::fetch_grouped('.');
+    list.sort_by(|a, b| a.get_name().cmp(b.get_name()));
+    assert_eq!(list.len(), 2);
+
+    let db = &list[0];
+    assert_eq!(db.get_name(), "db");
+    assert_eq!(db.get_exec_count(), 2);
+    let mut children: Vec<&str> = db.get_elements().iter().map(|c| c.get_name()).collect();
+    children.sort_unstable();
+    assert_eq!(children, vec!["db.a", "db.b"]);
+    let sum: Duration = db
+        .get_elements()
+        .iter()
+        .map(|c| c.get_total_duration())
+        .sum();
+    assert_eq!(db.get_total_duration(), sum);
+
+    let http = &list[1];
+    assert_eq!(http.get_name(), "http");
+    assert_eq!(http.get_elements().len(), 1);
+    assert_eq!(http.get_elements()[0].get_name(), "http.c");
+    assert_eq!(http.get_elements()[0].get_exec_percent(), 100);
+}
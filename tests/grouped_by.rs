@@ -0,0 +1,32 @@
+use exec_duration::ExecProbe;
+
+#[test]
+fn group_by_name_length_parity() {
+    for name in &["ab", "abcd", "abc", "abcdef", "a"] {
+        let mut ep = ExecProbe::new(name);
+        ep.add_point("part 1");
+    }
+
+    let mut list = exec_duration::fetch_grouped_by(|r| {
+        let parity = if r.get_name().len() % 2 == 0 {
+            "even"
+        } else {
+            "odd"
+        };
+        parity.to_string()
+    });
+    list.sort_by(|a, b| a.get_name().cmp(b.get_name()));
+    assert_eq!(list.len(), 2);
+    assert_eq!(list[0].get_name(), "even");
+    assert_eq!(list[0].get_exec_count(), 3);
+    assert_eq!(list[0].get_elements().len(), 3);
+    assert_eq!(list[1].get_name(), "odd");
+    assert_eq!(list[1].get_exec_count(), 2);
+    let mut odd: Vec<&str> = list[1]
+        .get_elements()
+        .iter()
+        .map(|c| c.get_name())
+        .collect();
+    odd.sort_unstable();
+    assert_eq!(odd, ["a", "abc"]);
+}
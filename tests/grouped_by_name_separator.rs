@@ -0,0 +1,34 @@
+use exec_duration::output;
+use exec_duration::ExecProbe;
+
+fn probe(name: &str) {
+    let mut ep = ExecProbe::new(name);
+    ep.add_point("part 1");
+}
+
+fn group_names() -> Vec<String> {
+    let mut names: Vec<String> = exec_duration::fetch_grouped_by_name_separator()
+        .iter()
+        .map(|r| r.get_name().to_string())
+        .collect();
+    names.sort_unstable();
+    names
+}
+
+#[test]
+fn configured_separator() {
+    probe("db::a");
+    probe("db::b");
+    probe("http::c");
+
+    // every probe makes a group on its own until a separator is set
+    assert_eq!(group_names(), vec!["db::a", "db::b", "http::c"]);
+
+    output::set_name_separator("::");
+    assert_eq!(group_names(), vec!["db", "http"]);
+    let text = exec_duration::fetch_grouped_by_name_separator()
+        .iter()
+        .map(|r| r.to_string())
+        .collect::<String>();
+    assert!(text.contains("[db::db::a] "), "{}", text);
+}
@@ -0,0 +1,14 @@
+use exec_duration::ExecProbe;
+
+#[test]
+fn has_data() {
+    assert!(!exec_duration::has_data());
+    // created but never measured
+    drop(ExecProbe::new("main"));
+    assert!(!exec_duration::has_data());
+    {
+        let mut ep = ExecProbe::new("main");
+        ep.add_point("part 1");
+    }
+    assert!(exec_duration::has_data());
+}
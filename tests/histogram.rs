@@ -0,0 +1,24 @@
+use exec_duration::ExecProbe;
+use std::thread::sleep;
+use std::time::Duration;
+
+#[test]
+fn bimodal_distribution() {
+    exec_duration::set_histogram(&[Duration::from_millis(1), Duration::from_secs(1)]);
+    // a few cache hits and mostly misses
+    for i in 0..10 {
+        let mut ep = ExecProbe::new("lookup");
+        if i % 3 == 0 {
+            ep.add_point("hit");
+        } else {
+            sleep(Duration::from_millis(2));
+            ep.add_point("miss");
+        }
+    }
+
+    let list = exec_duration::fetch_results();
+    let (lower, upper) = list[0].get_mode_bucket().unwrap();
+    assert_eq!(lower, Duration::from_millis(1));
+    assert_eq!(upper, Duration::from_secs(1));
+    assert_eq!(list[0].get_elements()[0].get_mode_bucket(), None);
+}
@@ -0,0 +1,25 @@
+use exec_duration::ExecProbe;
+
+#[test]
+fn conditional_point() {
+    for i in 0..10 {
+        let mut ep = ExecProbe::new("main");
+        if i % 2 == 0 {
+            ep.add_point("cache miss");
+        }
+        ep.add_point("always");
+        ep.add_point("always");
+    }
+
+    let list = exec_duration::fetch_results();
+    assert_eq!(list[0].get_hit_rate(), 1.0);
+    let element = |name: &str| {
+        list[0]
+            .get_elements()
+            .iter()
+            .find(|e| e.get_name() == name)
+            .unwrap()
+    };
+    assert!((element("cache miss").get_hit_rate() - 0.5).abs() < 1e-9);
+    assert_eq!(element("always").get_hit_rate(), 1.0);
+}
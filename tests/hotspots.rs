@@ -0,0 +1,21 @@
+use exec_duration::ExecProbe;
+use std::thread::sleep;
+use std::time::Duration;
+
+#[test]
+fn dominant_probe() {
+    for (name, ms) in &[("minor", 2), ("dominant", 40)] {
+        let mut ep = ExecProbe::new(name);
+        sleep(Duration::from_millis(*ms));
+        ep.add_point("part 1");
+    }
+
+    let list = exec_duration::fetch_hotspots(50.0);
+    let names: Vec<&str> = list.iter().map(|r| r.get_name()).collect();
+    assert_eq!(names, vec!["dominant"]);
+
+    let list = exec_duration::fetch_hotspots(0.0);
+    let names: Vec<&str> = list.iter().map(|r| r.get_name()).collect();
+    assert_eq!(names, vec!["dominant", "minor"]);
+    assert!(exec_duration::fetch_hotspots(100.0).is_empty());
+}
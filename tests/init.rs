@@ -0,0 +1,18 @@
+use exec_duration::ExecProbe;
+
+const NAME: &str = "after_init";
+
+#[test]
+fn double_init_is_idempotent() {
+    assert_eq!(exec_duration::init(), Ok(()));
+    {
+        let mut ep = ExecProbe::new(NAME);
+        ep.add_point("part 1");
+    }
+    assert_eq!(exec_duration::init(), Ok(()));
+
+    let list = exec_duration::fetch_results();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].get_name(), NAME);
+    assert_eq!(list[0].get_exec_count(), 1);
+}
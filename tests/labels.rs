@@ -0,0 +1,35 @@
+use exec_duration::ExecProbe;
+
+const NAME: &str = "http_request";
+
+fn request(method: &str, status: &str) {
+    let mut ep = ExecProbe::builder(NAME)
+        .label("method", method)
+        .label("status", status)
+        .build();
+    ep.add_point("part 1");
+}
+
+#[test]
+fn label_selector() {
+    request("GET", "200");
+    request("GET", "200");
+    request("GET", "500");
+    request("POST", "200");
+
+    let mut list = exec_duration::fetch_results_where(&[("method", "GET")]);
+    list.sort_by(|a, b| a.get_labels().cmp(b.get_labels()));
+    assert_eq!(list.len(), 2);
+    assert_eq!(list[0].get_name(), NAME);
+    assert_eq!(list[0].get_labels()["status"], "200");
+    assert_eq!(list[0].get_exec_count(), 2);
+    assert_eq!(list[1].get_labels()["status"], "500");
+
+    let list = exec_duration::fetch_results_where(&[("method", "GET"), ("status", "500")]);
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].get_exec_count(), 1);
+
+    assert_eq!(exec_duration::fetch_results_where(&[]).len(), 3);
+    assert!(exec_duration::fetch_results_where(&[("method", "PUT")]).is_empty());
+    assert_eq!(exec_duration::probe_names(), vec![NAME]);
+}
@@ -0,0 +1,19 @@
+use exec_duration::ExecProbe;
+
+#[test]
+fn opt_in() {
+    let untracked = ExecProbe::new("untracked");
+    exec_duration::flush_live();
+    assert!(exec_duration::fetch_results().is_empty());
+
+    exec_duration::set_live_tracking(true);
+    let tracked = ExecProbe::new("tracked");
+    exec_duration::flush_live();
+    let list = exec_duration::fetch_results();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].get_name(), "tracked");
+    assert_eq!(list[0].get_provisional_count(), 1);
+
+    drop(tracked);
+    drop(untracked);
+}
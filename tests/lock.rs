@@ -0,0 +1,22 @@
+use std::sync::Mutex;
+use std::thread::sleep;
+use std::time::Duration;
+
+const NAME: &str = "held";
+
+#[test]
+fn hold_duration_is_recorded() {
+    let mutex = Mutex::new(0);
+    {
+        let mut value = exec_duration::timed_lock(&mutex, NAME).unwrap();
+        *value += 1;
+        sleep(Duration::from_millis(30));
+    }
+    assert_eq!(*mutex.lock().unwrap(), 1);
+
+    let list = exec_duration::fetch_results();
+    let r = list.iter().find(|r| r.get_name() == NAME).unwrap();
+    assert_eq!(r.get_exec_count(), 1);
+    assert!(r.get_total_duration() >= Duration::from_millis(30));
+    assert!(r.get_total_duration() < Duration::from_millis(300));
+}
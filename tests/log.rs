@@ -0,0 +1,47 @@
+#![cfg(feature = "log")]
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::sync::Mutex;
+use std::time::Duration;
+
+static RECORDS: Mutex<Vec<(Level, String, String)>> = Mutex::new(Vec::new());
+
+struct CapturingLogger;
+
+impl Log for CapturingLogger {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        RECORDS.lock().unwrap().push((
+            record.level(),
+            record.target().to_string(),
+            record.args().to_string(),
+        ));
+    }
+
+    fn flush(&self) {}
+}
+
+#[test]
+fn measure_logged() {
+    log::set_logger(&CapturingLogger).unwrap();
+    log::set_max_level(LevelFilter::Trace);
+    {
+        let _m = exec_duration::measure_logged("load_config", Level::Warn);
+        std::thread::sleep(Duration::from_millis(2));
+    }
+
+    let records = RECORDS.lock().unwrap();
+    assert_eq!(records.len(), 1);
+    let (level, target, message) = &records[0];
+    assert_eq!(*level, Level::Warn);
+    assert_eq!(target, "exec_duration");
+    assert!(message.starts_with("[load_config] took "), "{}", message);
+    assert!(message.ends_with("ms"), "{}", message);
+
+    let list = exec_duration::fetch_results();
+    assert_eq!(list[0].get_name(), "load_config");
+    assert!(list[0].get_total_duration() >= Duration::from_millis(2));
+}
@@ -0,0 +1,19 @@
+use std::thread::sleep;
+use std::time::Duration;
+
+#[test]
+fn between_marks() {
+    exec_duration::mark("begin");
+    sleep(Duration::from_millis(20));
+    exec_duration::mark("end");
+
+    let d = exec_duration::between("begin", "end").unwrap();
+    assert!(d >= Duration::from_millis(20));
+    assert!(d < Duration::from_millis(200));
+    assert_eq!(exec_duration::between("end", "begin"), None);
+    assert_eq!(exec_duration::between("begin", "missing"), None);
+
+    // marks are per thread
+    let other = std::thread::spawn(|| exec_duration::between("begin", "end"));
+    assert_eq!(other.join().unwrap(), None);
+}
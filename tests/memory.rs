@@ -0,0 +1,14 @@
+use exec_duration::ExecProbe;
+
+#[test]
+fn estimate_grows_with_probes() {
+    let before = exec_duration::estimated_memory_bytes();
+    for i in 0..1000 {
+        let mut ep = ExecProbe::new(&format!("probe_{}", i));
+        ep.add_point("part 1");
+    }
+    let after = exec_duration::estimated_memory_bytes();
+    assert!(after > before);
+    // at least the names of the probes and points
+    assert!(after - before >= 1000 * ("probe_000".len() + "part 1".len()));
+}
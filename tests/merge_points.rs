@@ -0,0 +1,20 @@
+use exec_duration::ExecProbe;
+
+#[test]
+fn merged_loop_points() {
+    {
+        let mut ep = ExecProbe::builder("main").merge_points(true).build();
+        ep.add_point("setup");
+        for _ in 0..100 {
+            ep.add_point("iter");
+        }
+        assert_eq!(ep.points().len(), 2);
+        assert_eq!(ep.points()[1].get_name(), "iter");
+        assert_eq!(ep.points()[1].get_hits(), 100);
+    }
+
+    let list = exec_duration::fetch_results();
+    let iter = &list[0].get_elements()[1];
+    assert_eq!(iter.get_name(), "iter");
+    assert_eq!(iter.get_exec_count(), 100);
+}
@@ -0,0 +1,39 @@
+use exec_duration::output;
+use exec_duration::output::ExecDuration;
+use std::time::Duration;
+
+#[test]
+fn custom_separator() {
+    let sub = ExecDuration::builder("sub")
+        .count(1)
+        .total(Duration::from_millis(10))
+        .build()
+        .unwrap();
+    let part = ExecDuration::builder("part 1")
+        .count(1)
+        .total(Duration::from_millis(20))
+        .child(sub)
+        .build()
+        .unwrap();
+    let r = ExecDuration::builder("main")
+        .count(1)
+        .total(Duration::from_millis(50))
+        .child(part)
+        .build()
+        .unwrap();
+    let text = r.to_string();
+    let lines: Vec<&str> = text.lines().collect();
+    assert!(lines[1].starts_with("[main] [part 1] "), "{}", lines[1]);
+    assert!(lines[2].starts_with("[part 1] [sub] "), "{}", lines[2]);
+
+    output::set_name_separator("/");
+    let text = r.to_string();
+    let lines: Vec<&str> = text.lines().collect();
+    assert!(lines[0].starts_with("[main] "), "{}", lines[0]);
+    assert!(lines[1].starts_with("[main/part 1] "), "{}", lines[1]);
+    assert!(lines[2].starts_with("[main/part 1/sub] "), "{}", lines[2]);
+
+    // the elements left out are summed up under the path of their parent
+    output::set_max_display_children(0);
+    assert_eq!(r.to_string().lines().nth(1), Some("[main] (+1 more)"));
+}
@@ -0,0 +1,39 @@
+#![cfg(feature = "json")]
+
+use exec_duration::output::{self, ExecDuration};
+use std::time::Duration;
+
+fn node(name: &str, count: u64, ms: u64, childs: Vec<ExecDuration>) -> ExecDuration {
+    let mut builder = ExecDuration::builder(name)
+        .count(count)
+        .total(Duration::from_millis(ms));
+    for child in childs {
+        builder = builder.child(child);
+    }
+    builder.build().unwrap()
+}
+
+#[test]
+fn round_trip() {
+    let results = vec![
+        node("main", 2, 100, vec![node("part 1", 2, 60, vec![])]),
+        node("other", 1, 10, vec![]),
+    ];
+
+    let mut out = Vec::new();
+    output::to_ndjson(&results, &mut out).unwrap();
+    assert_eq!(String::from_utf8_lossy(&out).lines().count(), 2);
+    assert_eq!(output::from_ndjson(&mut out.as_slice()).unwrap(), results);
+
+    // duplicates are merged
+    let mut twice = out.clone();
+    twice.extend_from_slice(b"\n");
+    twice.extend_from_slice(&out);
+    let merged = output::from_ndjson(&mut twice.as_slice()).unwrap();
+    assert_eq!(merged.len(), 2);
+    assert_eq!(merged[0].get_exec_count(), 4);
+    assert_eq!(merged[0].get_total_duration(), Duration::from_millis(200));
+    assert_eq!(merged[0].get_elements()[0].get_exec_percent(), 60);
+
+    assert!(output::from_ndjson(&mut "{\"name\":".as_bytes()).is_err());
+}
@@ -0,0 +1,41 @@
+use exec_duration::ExecProbe;
+
+fn probe(name: &str) {
+    let mut ep = ExecProbe::new(name);
+    ep.add_point("part 1");
+}
+
+#[test]
+fn nested_collectors() {
+    let ((inner, after), outer) = exec_duration::scoped_collector(|| {
+        probe("outer");
+        let (_, inner) = exec_duration::scoped_collector(|| probe("inner"));
+        // the outer collector is installed again once the inner one is closed
+        probe("after");
+        (inner, exec_duration::fetch_results())
+    });
+
+    let names = |list: &[exec_duration::output::ExecDuration]| -> Vec<String> {
+        let mut names: Vec<String> = list.iter().map(|r| r.get_name().to_string()).collect();
+        names.sort_unstable();
+        names
+    };
+    assert_eq!(names(&inner), vec!["inner"]);
+    assert_eq!(names(&outer), vec!["after", "outer"]);
+    assert!(after.is_empty());
+    assert!(exec_duration::fetch_results().is_empty());
+
+    // a panic within the inner collector uninstalls it, the outer one keeps collecting
+    let (_, outer) = exec_duration::scoped_collector(|| {
+        let res = std::panic::catch_unwind(|| {
+            exec_duration::scoped_collector(|| {
+                probe("inner");
+                panic!("inner");
+            })
+        });
+        assert!(res.is_err());
+        probe("outer");
+    });
+    assert_eq!(names(&outer), vec!["outer"]);
+    assert!(exec_duration::fetch_results().is_empty());
+}
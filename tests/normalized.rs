@@ -0,0 +1,29 @@
+use exec_duration::ExecProbe;
+use std::thread::sleep;
+use std::time::Duration;
+
+fn workload(pause: Duration) -> String {
+    for name in ["zeta", "alpha", "mid"] {
+        let mut ep = ExecProbe::builder(name).label("run", "1").build();
+        sleep(pause);
+        ep.add_point("second");
+        ep.add_point("first");
+        ep.add_counter("items", 3);
+        // a tail after the last point in one run only
+        sleep(pause.saturating_sub(Duration::from_millis(1)));
+    }
+    let snapshot = exec_duration::fetch_results_normalized()
+        .iter()
+        .map(|r| format!("{}{:?}", r, r.get_counters()))
+        .collect();
+    exec_duration::shutdown();
+    snapshot
+}
+
+#[test]
+fn byte_stable() {
+    let first = workload(Duration::from_millis(1));
+    let second = workload(Duration::from_millis(15));
+    assert_eq!(first, second);
+    assert!(first.starts_with("[alpha] "), "{}", first);
+}
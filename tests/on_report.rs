@@ -0,0 +1,28 @@
+use exec_duration::{ExecProbe, Point};
+use std::sync::Mutex;
+use std::time::Duration;
+
+static REPORTS: Mutex<Vec<(String, Vec<String>)>> = Mutex::new(Vec::new());
+
+#[test]
+fn callback_invoked_per_report() {
+    exec_duration::on_report(|name: &str, total: Duration, points: &[Point]| {
+        let sum: Duration = points.iter().map(|p| p.get_duration()).sum();
+        assert!(sum <= total);
+        let points = points.iter().map(|p| p.get_name().to_string()).collect();
+        REPORTS.lock().unwrap().push((name.to_string(), points));
+    });
+
+    for name in &["probe_a", "probe_b", "probe_c"] {
+        let mut ep = ExecProbe::new(name);
+        ep.add_point("part 1");
+        ep.add_point("part 2");
+    }
+
+    let reports = REPORTS.lock().unwrap();
+    assert_eq!(reports.len(), 3);
+    for (r, name) in reports.iter().zip(&["probe_a", "probe_b", "probe_c"]) {
+        assert_eq!(r.0, *name);
+        assert_eq!(r.1, vec!["part 1", "part 2"]);
+    }
+}
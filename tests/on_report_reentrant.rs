@@ -0,0 +1,25 @@
+use exec_duration::{ExecProbe, Point};
+use std::time::Duration;
+
+#[test]
+fn callback_records_a_probe() {
+    exec_duration::on_report(|name: &str, _: Duration, _: &[Point]| {
+        if name != "audit" {
+            let mut ep = ExecProbe::new("audit");
+            ep.add_point("write");
+            ep.stop();
+            assert!(exec_duration::has_data());
+        }
+    });
+    {
+        let mut ep = ExecProbe::new("main");
+        ep.add_point("part 1");
+    }
+
+    let mut names: Vec<String> = exec_duration::fetch_results()
+        .iter()
+        .map(|r| r.get_name().to_string())
+        .collect();
+    names.sort_unstable();
+    assert_eq!(names, vec!["audit", "main"]);
+}
@@ -0,0 +1,44 @@
+#![cfg(feature = "otel")]
+
+use exec_duration::ExecProbe;
+use opentelemetry::metrics::MeterProvider;
+use opentelemetry_sdk::metrics::data::{AggregatedMetrics, MetricData};
+use opentelemetry_sdk::metrics::{InMemoryMetricExporter, SdkMeterProvider};
+
+#[test]
+fn instruments_are_created() {
+    for name in &["otel_a", "otel_b"] {
+        let mut ep = ExecProbe::new(name);
+        ep.add_point("part 1");
+        ep.add_point("part 2");
+    }
+
+    let exporter = InMemoryMetricExporter::default();
+    let provider = SdkMeterProvider::builder()
+        .with_periodic_exporter(exporter.clone())
+        .build();
+    let meter = provider.meter("exec_duration");
+    exec_duration::export_to_otel(&meter);
+    provider.force_flush().unwrap();
+
+    let metrics = exporter.get_finished_metrics().unwrap();
+    let mut names = Vec::new();
+    for rm in metrics.iter() {
+        for sm in rm.scope_metrics() {
+            for m in sm.metrics() {
+                names.push(m.name().to_string());
+                assert_eq!(m.unit(), "s");
+                match m.data() {
+                    AggregatedMetrics::F64(MetricData::Histogram(h)) => {
+                        // one series for the probe total, one per point and one for the tail
+                        assert_eq!(h.data_points().count(), 4);
+                    }
+                    _ => panic!("histogram expected"),
+                }
+            }
+        }
+    }
+    names.sort();
+    names.dedup();
+    assert_eq!(names, vec!["otel_a", "otel_b"]);
+}
@@ -0,0 +1,43 @@
+use exec_duration::output::ExecDuration;
+use exec_duration::ValidationError;
+use std::time::Duration;
+
+#[test]
+fn build_node() {
+    let part_1 = ExecDuration::builder("part 1")
+        .count(4)
+        .total(Duration::from_millis(30))
+        .build()
+        .unwrap();
+    let part_2 = ExecDuration::builder("part 2")
+        .count(2)
+        .total(Duration::from_millis(10))
+        .build()
+        .unwrap();
+    let r = ExecDuration::builder("main")
+        .count(2)
+        .total(Duration::from_millis(60))
+        .child(part_1)
+        .child(part_2)
+        .build()
+        .unwrap();
+
+    assert_eq!(r.get_name(), "main");
+    assert_eq!(r.get_exec_count(), 2);
+    assert_eq!(r.get_total_duration(), Duration::from_millis(60));
+    assert_eq!(r.get_avg_duration(), Duration::from_millis(30));
+    assert_eq!(r.get_exec_percent(), 100);
+    assert_eq!(r.get_depth(), 0);
+    assert_eq!(r.get_self_duration(), Duration::from_millis(20));
+    assert_eq!(r.get_elements().len(), 2);
+    let part = &r.get_elements()[0];
+    assert_eq!(part.get_name(), "part 1");
+    assert_eq!(part.get_exec_count(), 4);
+    assert_eq!(part.get_avg_duration(), Duration::from_micros(7500));
+    assert_eq!(part.get_exec_percent(), 50);
+    assert_eq!(part.get_depth(), 1);
+    assert_eq!(r.get_elements()[1].get_exec_percent(), 16);
+
+    let err = ExecDuration::builder("empty").build().unwrap_err();
+    assert_eq!(err, ValidationError::ZeroCount(String::from("empty")));
+}
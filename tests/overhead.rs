@@ -0,0 +1,25 @@
+use exec_duration::ExecProbe;
+use std::thread::sleep;
+use std::time::Duration;
+
+#[test]
+fn fine_grained_probes_cost_more() {
+    assert_eq!(exec_duration::overhead_ratio(), 0.0);
+
+    for _ in 0..2000 {
+        let mut ep = ExecProbe::new("tiny");
+        ep.add_point("line 1");
+    }
+    let tiny = exec_duration::overhead_ratio();
+    exec_duration::shutdown();
+
+    for _ in 0..3 {
+        let mut ep = ExecProbe::new("large");
+        sleep(Duration::from_millis(20));
+        ep.add_point("line 1");
+    }
+    let large = exec_duration::overhead_ratio();
+
+    assert!(large > 0.0);
+    assert!(tiny > large, "{} <= {}", tiny, large);
+}
@@ -0,0 +1,21 @@
+use exec_duration::ExecProbe;
+
+#[test]
+fn ratio_does_not_drain() {
+    exec_duration::set_auto_drain(true);
+    for _ in 0..100 {
+        let mut ep = ExecProbe::new("main");
+        ep.add_point("line 1");
+    }
+
+    assert!(exec_duration::overhead_ratio() > 0.0);
+    assert!(exec_duration::has_data());
+
+    // draining resets the overhead along with the measured durations
+    assert_eq!(exec_duration::fetch_results()[0].get_exec_count(), 100);
+    assert_eq!(exec_duration::overhead_ratio(), 0.0);
+    let mut ep = ExecProbe::new("main");
+    ep.add_point("line 1");
+    ep.stop();
+    assert!(exec_duration::overhead_ratio() > 0.0);
+}
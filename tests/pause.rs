@@ -0,0 +1,25 @@
+use exec_duration::ExecProbe;
+use std::thread::sleep;
+use std::time::Duration;
+
+const NAME: &str = "paused";
+
+#[test]
+fn paused_interval_is_excluded() {
+    let mut ep = ExecProbe::new(NAME);
+    sleep(Duration::from_millis(20));
+    ep.pause();
+    sleep(Duration::from_millis(200));
+    ep.resume();
+    sleep(Duration::from_millis(20));
+    ep.add_point("part 1");
+    ep.stop();
+
+    let list = exec_duration::fetch_results();
+    let r = list.iter().find(|r| r.get_name() == NAME).unwrap();
+    assert!(r.get_total_duration() >= Duration::from_millis(40));
+    assert!(r.get_total_duration() < Duration::from_millis(200));
+    let part = &r.get_elements()[0];
+    assert!(part.get_total_duration() >= Duration::from_millis(40));
+    assert!(part.get_total_duration() < Duration::from_millis(200));
+}
@@ -0,0 +1,16 @@
+use exec_duration::ExecProbe;
+use std::time::Duration;
+
+#[test]
+fn injected_point() {
+    {
+        let mut ep = ExecProbe::new("main");
+        ep.add_point_duration("external", Duration::from_millis(42));
+        assert_eq!(ep.points()[0].get_duration(), Duration::from_millis(42));
+    }
+
+    let list = exec_duration::fetch_results();
+    let element = &list[0].get_elements()[0];
+    assert_eq!(element.get_name(), "external");
+    assert_eq!(element.get_total_duration(), Duration::from_millis(42));
+}
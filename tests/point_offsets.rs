@@ -0,0 +1,29 @@
+use exec_duration::ExecProbe;
+use std::thread::sleep;
+use std::time::Duration;
+
+#[test]
+fn offsets_are_running_totals() {
+    let mut ep = ExecProbe::new("main");
+    for (name, ms) in [("part 1", 10), ("part 2", 5), ("part 3", 20)].iter() {
+        sleep(Duration::from_millis(*ms));
+        ep.add_point(name);
+    }
+
+    let offsets: Vec<(&str, Duration, Duration)> = ep.point_offsets().collect();
+    let points = ep.points();
+    assert_eq!(offsets.len(), 3);
+    assert_eq!(
+        offsets[0],
+        ("part 1", points[0].get_duration(), points[0].get_duration())
+    );
+    assert_eq!(offsets[1].0, "part 2");
+    assert_eq!(
+        offsets[1].1,
+        points[0].get_duration() + points[1].get_duration()
+    );
+    assert_eq!(offsets[2].0, "part 3");
+    assert_eq!(offsets[2].1, offsets[1].1 + points[2].get_duration());
+    assert_eq!(offsets[2].2, points[2].get_duration());
+    assert!(offsets[2].1 >= Duration::from_millis(35));
+}
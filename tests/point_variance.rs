@@ -0,0 +1,24 @@
+use exec_duration::ExecProbe;
+use std::time::Duration;
+
+#[test]
+fn most_variable_point() {
+    for i in 0..10 {
+        let mut ep = ExecProbe::new("main");
+        ep.add_point_duration("fixed", Duration::from_millis(1));
+        let jittery = if i % 2 == 0 { 100 } else { 10_000 };
+        ep.add_point_duration("variable", Duration::from_micros(jittery));
+    }
+
+    let list = exec_duration::fetch_results();
+    let r = &list[0];
+    let fixed = &r.get_elements()[0];
+    assert_eq!(fixed.get_name(), "fixed");
+    assert_eq!(fixed.get_variance(), Some(0.0));
+
+    let variable = r.most_variable_element().unwrap();
+    assert_eq!(variable.get_name(), "variable");
+    // half at 0.1ms, half at 10ms: the standard deviation is 4.95ms
+    let std_dev = variable.get_variance().unwrap().sqrt();
+    assert!((std_dev - 4_950_000.0).abs() < 1.0, "{}", std_dev);
+}
@@ -0,0 +1,15 @@
+use exec_duration::ExecProbe;
+
+#[test]
+fn recorded_names() {
+    for name in &["probe_a", "probe_b", "probe_c"] {
+        let mut ep = ExecProbe::new(name);
+        ep.add_point("part 1");
+    }
+    // created but never measured
+    let _ep = ExecProbe::new("probe_d");
+
+    let mut names = exec_duration::probe_names();
+    names.sort();
+    assert_eq!(names, vec!["probe_a", "probe_b", "probe_c"]);
+}
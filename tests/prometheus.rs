@@ -0,0 +1,41 @@
+#![cfg(feature = "prometheus")]
+
+use exec_duration::{ExecProbe, ValidationError};
+
+#[test]
+fn described_and_default_families() {
+    exec_duration::describe(
+        "http request",
+        "Handling of an HTTP request",
+        "milliseconds",
+    )
+    .unwrap();
+    assert_eq!(
+        exec_duration::describe("db", "Database query", "hours"),
+        Err(ValidationError::UnknownUnit(String::from("hours")))
+    );
+    {
+        let mut ep = ExecProbe::builder("http request")
+            .label("method", "GET")
+            .build();
+        ep.add_point("parse");
+    }
+    {
+        let mut ep = ExecProbe::new("db");
+        ep.add_point("query");
+    }
+
+    let text = exec_duration::export_to_prometheus();
+    let lines: Vec<&str> = text.lines().collect();
+    assert!(lines.contains(&"# HELP http_request_milliseconds Handling of an HTTP request"));
+    assert!(lines.contains(&"# TYPE http_request_milliseconds summary"));
+    assert!(lines.contains(&"# UNIT http_request_milliseconds milliseconds"));
+    assert!(lines.contains(&"http_request_milliseconds_count{method=\"GET\"} 1"));
+    assert!(lines.contains(&"http_request_milliseconds_count{method=\"GET\",point=\"parse\"} 1"));
+
+    assert!(lines.contains(&"# HELP db_seconds Execution duration of the db probe"));
+    assert!(lines.contains(&"# TYPE db_seconds summary"));
+    assert!(!lines.iter().any(|l| l.starts_with("# UNIT db_seconds")));
+    assert!(lines.contains(&"db_seconds_count 1"));
+    assert!(lines.contains(&"db_seconds_count{point=\"query\"} 1"));
+}
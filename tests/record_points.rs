@@ -0,0 +1,22 @@
+use exec_duration::ExecProbe;
+use std::thread::sleep;
+use std::time::Duration;
+
+const NAME: &str = "muted";
+
+#[test]
+fn points_are_discarded() {
+    let mut ep = ExecProbe::builder(NAME).record_points(false).build();
+    sleep(Duration::from_millis(10));
+    ep.add_point("part 1");
+    sleep(Duration::from_millis(10));
+    ep.add_point("part 2");
+    ep.add_point("part 3");
+    ep.stop();
+
+    let list = exec_duration::fetch_results();
+    let r = list.iter().find(|r| r.get_name() == NAME).unwrap();
+    assert_eq!(r.get_exec_count(), 1);
+    assert!(r.get_total_duration() >= Duration::from_millis(20));
+    assert!(r.get_elements().is_empty());
+}
@@ -0,0 +1,21 @@
+#[test]
+fn declared_probes() {
+    assert!(exec_duration::registered_probes().is_empty());
+    exec_duration::declare("parse", "parsing of the configuration file");
+    exec_duration::declare("connect", "connection to the database");
+
+    assert_eq!(
+        exec_duration::registered_probes(),
+        vec![
+            (
+                String::from("connect"),
+                String::from("connection to the database")
+            ),
+            (
+                String::from("parse"),
+                String::from("parsing of the configuration file")
+            ),
+        ]
+    );
+    assert!(!exec_duration::has_data());
+}
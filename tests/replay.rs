@@ -0,0 +1,49 @@
+use exec_duration::{ExecProbe, Point, RawEvent};
+use std::sync::Mutex;
+use std::thread::sleep;
+use std::time::Duration;
+
+static EVENTS: Mutex<Vec<RawEvent>> = Mutex::new(Vec::new());
+
+#[test]
+fn replay_matches_online() {
+    exec_duration::on_report(|name: &str, total: Duration, points: &[Point]| {
+        EVENTS
+            .lock()
+            .unwrap()
+            .push(RawEvent::new(name, total, points.to_vec()));
+    });
+    for ms in [1, 3, 2] {
+        let mut ep = ExecProbe::new("main");
+        sleep(Duration::from_millis(ms));
+        ep.add_point("part 1");
+        ep.add_point("part 2");
+    }
+    {
+        let mut ep = ExecProbe::new("other");
+        ep.add_point("part 1");
+    }
+
+    let mut online = exec_duration::fetch_results();
+    let mut replayed = exec_duration::replay(&EVENTS.lock().unwrap());
+    online.sort_by(|a, b| a.get_name().cmp(b.get_name()));
+    replayed.sort_by(|a, b| a.get_name().cmp(b.get_name()));
+    assert_eq!(online.len(), replayed.len());
+    for (a, b) in online.iter().zip(replayed.iter()) {
+        assert_eq!(a.summary(), b.summary());
+        assert_eq!(a.get_jitter(), b.get_jitter());
+        assert_eq!(a.get_cv(), b.get_cv());
+        assert_eq!(a.get_elements(), b.get_elements());
+    }
+
+    // the events are aggregated again with other settings
+    exec_duration::set_sample_clamp(Duration::from_millis(2));
+    let replayed = exec_duration::replay(&EVENTS.lock().unwrap());
+    let main = replayed.iter().find(|r| r.get_name() == "main").unwrap();
+    assert_eq!(main.get_exec_count(), 1);
+    assert_eq!(main.get_clamped_count(), 2);
+    // the collected metrics are left untouched
+    let mut list = exec_duration::fetch_results();
+    list.sort_by(|a, b| a.get_name().cmp(b.get_name()));
+    assert_eq!(list, online);
+}
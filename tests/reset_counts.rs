@@ -0,0 +1,33 @@
+use exec_duration::ExecProbe;
+use std::time::Duration;
+
+fn probe() {
+    let mut ep = ExecProbe::new("main");
+    ep.add_point("part 1");
+}
+
+#[test]
+fn counts_reset_rolling_metrics_kept() {
+    exec_duration::set_window(Duration::from_secs(1), 60);
+    exec_duration::set_ema_alpha(0.5);
+    for _ in 0..5 {
+        probe();
+    }
+    let total = exec_duration::fetch_results()[0].get_total_duration();
+    let ema = exec_duration::fetch_results()[0].get_ema();
+    assert!(ema.is_some());
+
+    exec_duration::reset_counts();
+    let list = exec_duration::fetch_results();
+    assert_eq!(list[0].get_exec_count(), 0);
+    assert_eq!(list[0].get_elements()[0].get_exec_count(), 0);
+    assert_eq!(list[0].get_total_duration(), total);
+    assert_eq!(list[0].get_ema(), ema);
+
+    let recent = exec_duration::fetch_results_window(Duration::from_secs(60));
+    assert_eq!(recent[0].get_exec_count(), 5);
+    assert_eq!(recent[0].get_total_duration(), total);
+
+    probe();
+    assert_eq!(exec_duration::fetch_results()[0].get_exec_count(), 1);
+}
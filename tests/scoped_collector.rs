@@ -0,0 +1,34 @@
+use exec_duration::ExecProbe;
+
+#[test]
+fn scoped_collector() {
+    {
+        let mut ep = ExecProbe::new("global");
+        ep.add_point("part 1");
+    }
+
+    let (value, results) = exec_duration::scoped_collector(|| {
+        for _ in 0..2 {
+            let mut ep = ExecProbe::new("scoped");
+            ep.add_point("part 1");
+        }
+        "done"
+    });
+    assert_eq!(value, "done");
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].get_name(), "scoped");
+    assert_eq!(results[0].get_exec_count(), 2);
+
+    // the global store is unaffected
+    let list = exec_duration::fetch_results();
+    let names: Vec<&str> = list.iter().map(|r| r.get_name()).collect();
+    assert_eq!(names, vec!["global"]);
+    assert_eq!(list[0].get_exec_count(), 1);
+
+    // probes created after the scope are recorded globally again
+    {
+        let mut ep = ExecProbe::new("scoped");
+        ep.add_point("part 1");
+    }
+    assert_eq!(exec_duration::probe_names(), vec!["global", "scoped"]);
+}
@@ -0,0 +1,26 @@
+use exec_duration::ExecProbe;
+use std::thread::sleep;
+use std::time::Duration;
+
+#[test]
+fn tail_is_self_time() {
+    {
+        let mut ep = ExecProbe::new("main");
+        sleep(Duration::from_millis(10));
+        ep.add_point("part 1");
+        sleep(Duration::from_millis(20));
+    }
+
+    let list = exec_duration::fetch_results();
+    let r = &list[0];
+    assert_eq!(
+        r.get_elements().last().unwrap().get_name(),
+        exec_duration::output::TAIL
+    );
+    assert!(
+        r.get_self_duration() >= Duration::from_millis(20),
+        "{:?}",
+        r.get_self_duration()
+    );
+    assert!(r.get_self_duration() < r.get_total_duration());
+}
@@ -0,0 +1,25 @@
+use exec_duration::ExecProbe;
+use more_asserts::{assert_ge, assert_lt};
+use std::thread::sleep;
+use std::time::Duration;
+
+#[test]
+fn wait_and_service() {
+    {
+        let mut ep = ExecProbe::new("task");
+        sleep(Duration::from_millis(30));
+        ep.mark_service_start();
+        sleep(Duration::from_millis(60));
+    }
+
+    let list = exec_duration::fetch_results();
+    let elements = list[0].get_elements();
+
+    assert_eq!(elements[0].get_name(), "wait");
+    assert_ge!(elements[0].get_total_duration(), Duration::from_millis(30));
+    assert_lt!(elements[0].get_total_duration(), Duration::from_millis(60));
+
+    assert_eq!(elements[1].get_name(), "service");
+    assert_ge!(elements[1].get_total_duration(), Duration::from_millis(60));
+    assert_lt!(elements[1].get_total_duration(), Duration::from_millis(90));
+}
@@ -0,0 +1,31 @@
+use exec_duration::ExecProbe;
+
+fn probe(name: &str) {
+    let mut ep = ExecProbe::new(name);
+    ep.add_point("line 1");
+}
+
+fn names(session: &str) -> Vec<String> {
+    let mut names: Vec<String> = exec_duration::fetch_session_results(session)
+        .iter()
+        .map(|r| r.get_name().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn sessions() {
+    exec_duration::with_session("startup", || {
+        probe("load_config");
+        exec_duration::with_session("plugins", || probe("load_plugin"));
+    });
+    exec_duration::with_session("steady", || probe("serve"));
+    probe("outside");
+
+    assert_eq!(names("startup"), ["load_config", "load_plugin"]);
+    assert_eq!(names("plugins"), ["load_plugin"]);
+    assert_eq!(names("steady"), ["serve"]);
+    assert!(names("shutdown").is_empty());
+    assert_eq!(exec_duration::fetch_results().len(), 4);
+}
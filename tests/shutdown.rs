@@ -0,0 +1,25 @@
+use exec_duration::ExecProbe;
+
+fn probe(name: &str) {
+    let mut ep = ExecProbe::new(name);
+    ep.add_point("part 1");
+}
+
+#[test]
+fn record_after_shutdown() {
+    probe("before");
+    assert_eq!(exec_duration::probe_names(), vec!["before"]);
+
+    exec_duration::shutdown();
+    assert!(exec_duration::fetch_results().is_empty());
+
+    probe("after");
+    let list = exec_duration::fetch_results();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].get_name(), "after");
+    assert_eq!(list[0].get_exec_count(), 1);
+
+    exec_duration::shutdown();
+    assert_eq!(exec_duration::init(), Ok(()));
+    assert!(!exec_duration::probe_names().contains(&String::from("after")));
+}
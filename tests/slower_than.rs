@@ -0,0 +1,21 @@
+use exec_duration::ExecProbe;
+use std::thread::sleep;
+use std::time::Duration;
+
+#[test]
+fn only_slow_runs_are_recorded() {
+    for pause in [Duration::from_millis(0), Duration::from_millis(50)] {
+        let mut ep = ExecProbe::builder("main")
+            .record_if_slower_than(Duration::from_millis(20))
+            .build();
+        sleep(pause);
+        ep.add_point("line 1");
+    }
+
+    let list = exec_duration::fetch_results();
+    let r = list.iter().find(|r| r.get_name() == "main").unwrap();
+    assert_eq!(r.get_exec_count(), 1);
+    assert_eq!(r.get_fast_count(), 1);
+    assert!(r.get_total_duration() >= Duration::from_millis(50));
+    assert_eq!(r.get_elements()[0].get_exec_count(), 1);
+}
@@ -0,0 +1,33 @@
+#![cfg(feature = "statsd")]
+
+use exec_duration::ExecProbe;
+use std::net::UdpSocket;
+use std::time::Duration;
+
+#[test]
+fn timing_packets() {
+    {
+        let mut ep = ExecProbe::new("main");
+        ep.add_point_duration("part 1", Duration::from_millis(42));
+    }
+
+    let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+    server
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    exec_duration::export_to_statsd(&server.local_addr().unwrap().to_string());
+
+    let mut packets = Vec::new();
+    let mut buf = [0; 512];
+    for _ in 0..2 {
+        let (n, _) = server.recv_from(&mut buf).unwrap();
+        packets.push(String::from_utf8(buf[..n].to_vec()).unwrap());
+    }
+    assert!(packets[0].starts_with("main:"), "{}", packets[0]);
+    assert!(packets[0].ends_with("|ms"), "{}", packets[0]);
+    assert_eq!(packets[1], "main.part_1:42.000|ms");
+
+    // unreachable endpoints are ignored
+    exec_duration::export_to_statsd("not an address");
+    exec_duration::export_to_statsd("127.0.0.1:1");
+}
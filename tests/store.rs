@@ -0,0 +1,82 @@
+use exec_duration::output::{ExecDuration, SortKey};
+use exec_duration::{ExecProbe, Point, Store};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Probe name and point names of each execution
+type Reports = Vec<(String, Vec<String>)>;
+
+#[derive(Clone, Default)]
+struct MockStore {
+    reports: Arc<Mutex<Reports>>,
+}
+
+impl Store for MockStore {
+    fn report(&mut self, name: &str, _: &BTreeMap<String, String>, _: Duration, points: &[Point]) {
+        let points = points.iter().map(|p| p.get_name().to_string()).collect();
+        self.reports
+            .lock()
+            .unwrap()
+            .push((name.to_string(), points));
+    }
+
+    fn fetch(&self) -> Vec<ExecDuration> {
+        let reports = self.reports.lock().unwrap();
+        match reports.len() {
+            0 => Vec::new(),
+            n => vec![ExecDuration::builder("mock")
+                .count(n as u64)
+                .build()
+                .unwrap()],
+        }
+    }
+
+    fn reset(&mut self) {
+        self.reports.lock().unwrap().clear();
+    }
+}
+
+#[test]
+fn reports_reach_the_store() {
+    let store = MockStore::default();
+    exec_duration::set_store(store.clone());
+    for _ in 0..2 {
+        let mut ep = ExecProbe::new("main");
+        ep.add_point("part 1");
+    }
+
+    assert_eq!(
+        *store.reports.lock().unwrap(),
+        vec![
+            ("main".to_string(), vec!["part 1".to_string()]),
+            ("main".to_string(), vec!["part 1".to_string()]),
+        ]
+    );
+    let list = exec_duration::fetch_results();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].get_name(), "mock");
+    assert_eq!(list[0].get_exec_count(), 2);
+    assert_eq!(exec_duration::probe_names(), vec!["mock".to_string()]);
+    assert!(exec_duration::has_data());
+    let top = exec_duration::fetch_top(1, SortKey::ExecCount);
+    assert_eq!(top.len(), 1);
+    assert_eq!(top[0].get_exec_count(), 2);
+
+    // sessions are still recorded next to the store
+    exec_duration::with_session("startup", || {
+        let mut ep = ExecProbe::new("init");
+        ep.add_point("part 1");
+    });
+    assert_eq!(exec_duration::fetch_session_results("startup").len(), 1);
+    assert_eq!(store.reports.lock().unwrap().len(), 3);
+
+    // executions aggregated elsewhere go to the store as well
+    exec_duration::bulk_import(&[("batch".to_string(), 2, Duration::from_millis(4))]);
+    assert_eq!(store.reports.lock().unwrap().len(), 5);
+    assert_eq!(exec_duration::fetch_results().len(), 1);
+
+    exec_duration::set_auto_drain(true);
+    assert_eq!(exec_duration::fetch_results().len(), 1);
+    assert!(store.reports.lock().unwrap().is_empty());
+}
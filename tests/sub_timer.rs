@@ -0,0 +1,33 @@
+use exec_duration::ExecProbe;
+use std::thread::sleep;
+use std::time::Duration;
+
+#[test]
+fn overlapping_sub_timers() {
+    {
+        let mut ep = ExecProbe::new("main");
+        let download = ep.timer("download");
+        sleep(Duration::from_millis(20));
+        let decode = ep.timer("decode");
+        sleep(Duration::from_millis(10));
+        drop(download);
+        sleep(Duration::from_millis(30));
+        drop(decode);
+    }
+
+    let list = exec_duration::fetch_results();
+    assert_eq!(list.len(), 1);
+    let element = |name: &str| {
+        list[0]
+            .get_elements()
+            .iter()
+            .find(|e| e.get_name() == name)
+            .unwrap()
+            .get_total_duration()
+    };
+    let (download, decode) = (element("download"), element("decode"));
+    assert!(download >= Duration::from_millis(30), "{:?}", download);
+    assert!(decode >= Duration::from_millis(40), "{:?}", decode);
+    // the spans overlap, their sum exceeds the execution time
+    assert!(download + decode > list[0].get_total_duration());
+}
@@ -0,0 +1,26 @@
+#![cfg(feature = "json")]
+
+use exec_duration::output::ExecDuration;
+use std::time::Duration;
+
+#[test]
+fn summary_has_no_children() {
+    let part = ExecDuration::builder("part 1")
+        .count(2)
+        .total(Duration::from_nanos(100))
+        .build()
+        .unwrap();
+    let main = ExecDuration::builder("main")
+        .count(2)
+        .total(Duration::from_nanos(300))
+        .child(part)
+        .build()
+        .unwrap();
+
+    let json = serde_json::to_value(main.summary()).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({"name": "main", "count": 2, "total": 300, "avg": 150})
+    );
+    assert!(!json.to_string().contains("part 1"));
+}
@@ -0,0 +1,23 @@
+use exec_duration::{ExecProbe, ExecProbeManager};
+
+#[test]
+fn double_buffer() {
+    {
+        let mut ep = ExecProbe::new("before");
+        ep.add_point("line 1");
+    }
+
+    let previous = exec_duration::swap(ExecProbeManager::new());
+    {
+        let mut ep = ExecProbe::new("after");
+        ep.add_point("line 1");
+    }
+
+    let list = previous.fetch_results();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].get_name(), "before");
+
+    let list = exec_duration::fetch_results();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].get_name(), "after");
+}
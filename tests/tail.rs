@@ -0,0 +1,29 @@
+use exec_duration::output;
+use exec_duration::ExecProbe;
+use std::thread::sleep;
+use std::time::Duration;
+
+#[test]
+fn tail_accounts_for_the_remainder() {
+    {
+        let mut ep = ExecProbe::new("main");
+        sleep(Duration::from_millis(10));
+        ep.add_point("part 1");
+        sleep(Duration::from_millis(10));
+        ep.add_point("part 2");
+        sleep(Duration::from_millis(20));
+    }
+
+    let list = exec_duration::fetch_results();
+    let r = list.iter().find(|r| r.get_name() == "main").unwrap();
+    let elements = r.get_elements();
+    assert_eq!(elements.len(), 3);
+    let tail = &elements[2];
+    assert_eq!(tail.get_name(), output::TAIL);
+    assert_eq!(tail.get_exec_count(), 1);
+    assert!(tail.get_total_duration() >= Duration::from_millis(20));
+    let sum: Duration = elements.iter().map(|e| e.get_total_duration()).sum();
+    assert_eq!(sum, r.get_total_duration());
+    let percent: f64 = elements.iter().map(|e| e.get_exec_percent_f64()).sum();
+    assert!((percent - 100.0).abs() < 1e-9);
+}
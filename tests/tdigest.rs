@@ -0,0 +1,22 @@
+#![cfg(feature = "tdigest")]
+
+use exec_duration::ExecProbe;
+use std::thread::sleep;
+use std::time::Duration;
+
+#[test]
+fn percentiles() {
+    for i in 0..20 {
+        let mut ep = ExecProbe::new("main");
+        if i == 19 {
+            sleep(Duration::from_millis(50));
+        }
+        ep.add_point("line 1");
+    }
+
+    let list = exec_duration::fetch_results();
+    let r = list.iter().find(|r| r.get_name() == "main").unwrap();
+    assert!(r.get_percentile(50.0).unwrap() < Duration::from_millis(10));
+    assert!(r.get_percentile(100.0).unwrap() >= Duration::from_millis(50));
+    assert_eq!(r.get_elements()[0].get_percentile(50.0), None);
+}
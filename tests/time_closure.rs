@@ -0,0 +1,13 @@
+use std::thread::sleep;
+use std::time::Duration;
+
+#[test]
+fn value_and_duration() {
+    let (value, duration) = exec_duration::time_closure(|| {
+        sleep(Duration::from_millis(20));
+        "done"
+    });
+    assert_eq!(value, "done");
+    assert!(duration >= Duration::from_millis(20));
+    assert!(exec_duration::fetch_results().is_empty());
+}
@@ -0,0 +1,29 @@
+#![cfg(feature = "derive")]
+
+use exec_duration::Timed;
+
+#[derive(Timed)]
+struct Compaction {
+    files: Vec<u32>,
+}
+
+#[derive(Timed)]
+struct Flush<T> {
+    value: T,
+}
+
+#[test]
+fn probe_named_after_the_type() {
+    let op = Compaction {
+        files: vec![1, 2, 3],
+    };
+    let merged = op.timed_run(|op| op.files.iter().sum::<u32>());
+    assert_eq!(merged, 6);
+    Flush { value: 1u8 }.timed_run(|f| assert_eq!(f.value, 1));
+
+    let list = exec_duration::fetch_results();
+    let mut names: Vec<&str> = list.iter().map(|r| r.get_name()).collect();
+    names.sort();
+    assert_eq!(names, ["Compaction", "Flush"]);
+    assert_eq!(list[0].get_exec_count(), 1);
+}
@@ -0,0 +1,24 @@
+#[test]
+fn attempts_as_points() {
+    let mut calls = 0;
+    let res: Result<&str, String> = exec_duration::timed_retry("fetch", 5, |attempt| {
+        calls += 1;
+        if attempt < 2 {
+            Err(format!("attempt {} failed", attempt))
+        } else {
+            Ok("done")
+        }
+    });
+    assert_eq!(res, Ok("done"));
+    assert_eq!(calls, 3);
+
+    let res: Result<(), u32> = exec_duration::timed_retry("always_failing", 2, Err);
+    assert_eq!(res, Err(1));
+
+    let list = exec_duration::fetch_results();
+    let r = list.iter().find(|r| r.get_name() == "fetch").unwrap();
+    assert_eq!(r.get_exec_count(), 1);
+    let names: Vec<&str> = r.get_elements().iter().map(|e| e.get_name()).collect();
+    assert_eq!(names[..3], ["attempt_0", "attempt_1", "attempt_2"]);
+    assert!(!names.contains(&"attempt_3"));
+}
@@ -0,0 +1,27 @@
+use exec_duration::Token;
+use std::thread::sleep;
+use std::time::Duration;
+
+const NAME: &str = "callback";
+
+fn begin() -> Token {
+    exec_duration::start(NAME)
+}
+
+fn end(token: Token) {
+    exec_duration::finish(token);
+}
+
+#[test]
+fn start_and_finish_in_different_frames() {
+    let token = begin();
+    sleep(Duration::from_millis(20));
+    end(token);
+
+    let list = exec_duration::fetch_results();
+    let r = list.iter().find(|r| r.get_name() == NAME).unwrap();
+    assert_eq!(r.get_exec_count(), 1);
+    assert!(r.get_total_duration() >= Duration::from_millis(20));
+    assert!(r.get_total_duration() < Duration::from_millis(200));
+    assert!(r.get_elements().is_empty());
+}
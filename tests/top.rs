@@ -0,0 +1,20 @@
+use exec_duration::output::SortKey;
+use exec_duration::ExecProbe;
+use std::thread::sleep;
+use std::time::Duration;
+
+#[test]
+fn two_slowest() {
+    for (name, ms) in &[("p1", 1), ("p2", 30), ("p3", 5), ("p4", 60), ("p5", 10)] {
+        let mut ep = ExecProbe::new(name);
+        sleep(Duration::from_millis(*ms));
+        ep.add_point("part 1");
+    }
+
+    let list = exec_duration::fetch_top(2, SortKey::TotalDuration);
+    let names: Vec<&str> = list.iter().map(|r| r.get_name()).collect();
+    assert_eq!(names, vec!["p4", "p2"]);
+    assert_eq!(list[0].get_elements()[0].get_name(), "part 1");
+    assert!(exec_duration::fetch_top(0, SortKey::ExecCount).is_empty());
+    assert_eq!(exec_duration::fetch_top(10, SortKey::AvgDuration).len(), 5);
+}
@@ -0,0 +1,25 @@
+use exec_duration::ExecProbe;
+use std::time::Duration;
+
+#[test]
+fn total_matching() {
+    for name in ["db.a", "db.b", "http.c"].iter() {
+        let mut ep = ExecProbe::new(name);
+        std::thread::sleep(Duration::from_millis(1));
+        ep.add_point("query");
+    }
+    let results = exec_duration::fetch_results();
+    let total_of = |name: &str| {
+        results
+            .iter()
+            .find(|r| r.get_name() == name)
+            .unwrap()
+            .get_total_duration()
+    };
+    assert_eq!(
+        exec_duration::total_matching("db"),
+        total_of("db.a") + total_of("db.b")
+    );
+    assert_eq!(exec_duration::total_matching("http"), total_of("http.c"));
+    assert_eq!(exec_duration::total_matching("grpc"), Duration::ZERO);
+}
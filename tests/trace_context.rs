@@ -0,0 +1,48 @@
+use exec_duration::{ExecProbe, Point, TraceContext};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const TRACE_ID: u128 = 0x4bf9_2f35_77b3_4da6_a3ce_929d_0e0e_4736;
+const SPAN_ID: u64 = 0x00f0_67aa_0ba9_02b7;
+
+#[test]
+fn ids_survive_into_the_report() {
+    let reported = Arc::new(Mutex::new(Vec::new()));
+    let r = reported.clone();
+    exec_duration::on_report_traced(
+        move |name: &str, _: Duration, _: &[Point], ctx: Option<&TraceContext>| {
+            r.lock().unwrap().push((name.to_string(), ctx.copied()));
+        },
+    );
+
+    {
+        let mut ep = ExecProbe::with_trace_context("traced", TRACE_ID, SPAN_ID);
+        ep.add_point("part 1");
+        #[cfg(feature = "json")]
+        {
+            let json = serde_json::to_string(&ep).unwrap();
+            let expected = format!(
+                r#""trace":{{"trace_id":{},"span_id":{}}}"#,
+                TRACE_ID, SPAN_ID
+            );
+            assert!(json.contains(&expected), "{}", json);
+        }
+    }
+    {
+        let mut ep = ExecProbe::new("untraced");
+        ep.add_point("part 1");
+    }
+
+    let reported = reported.lock().unwrap();
+    let expected = TraceContext {
+        trace_id: TRACE_ID,
+        span_id: SPAN_ID,
+    };
+    assert_eq!(
+        *reported,
+        vec![
+            (String::from("traced"), Some(expected)),
+            (String::from("untraced"), None)
+        ]
+    );
+}
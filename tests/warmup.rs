@@ -0,0 +1,16 @@
+use exec_duration::ExecProbe;
+
+const NAME: &str = "warm";
+
+#[test]
+fn first_runs_are_discarded() {
+    for _ in 0..10 {
+        let mut ep = ExecProbe::builder(NAME).warmup(3).build();
+        ep.add_point("part 1");
+    }
+
+    let list = exec_duration::fetch_results();
+    let r = list.iter().find(|r| r.get_name() == NAME).unwrap();
+    assert_eq!(r.get_exec_count(), 7);
+    assert_eq!(r.get_elements()[0].get_exec_count(), 7);
+}
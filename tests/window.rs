@@ -0,0 +1,38 @@
+use exec_duration::ExecProbe;
+use std::thread::sleep;
+use std::time::Duration;
+
+const NAME: &str = "windowed";
+
+fn probe() {
+    let mut ep = ExecProbe::new(NAME);
+    ep.add_point("part 1");
+}
+
+#[test]
+fn only_recent_activity() {
+    let width = Duration::from_millis(50);
+    let window = Duration::from_millis(100);
+    exec_duration::set_window(width, 10);
+
+    for _ in 0..5 {
+        probe();
+    }
+    let list = exec_duration::fetch_results_window(window);
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].get_exec_count(), 5);
+
+    sleep(window + 2 * width);
+    assert!(exec_duration::fetch_results_window(window).is_empty());
+
+    for _ in 0..2 {
+        probe();
+    }
+    let list = exec_duration::fetch_results_window(window);
+    assert_eq!(list[0].get_exec_count(), 2);
+    assert!(list[0].get_elements().is_empty());
+
+    // lifetime aggregates are untouched
+    let list = exec_duration::fetch_results();
+    assert_eq!(list[0].get_exec_count(), 7);
+}